@@ -1,52 +1,193 @@
-use std::sync::Arc;
+use std::{
+    net::IpAddr,
+    sync::{Arc, LazyLock},
+};
 
 use actix_web::HttpRequest;
 use argon2::{
-    Argon2,
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+    Algorithm, Argon2, Params, Version,
+    password_hash::{
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        rand_core::{OsRng, RngCore},
+    },
 };
+use axum::http::{HeaderMap, Method, header};
 use axum_extra::extract::CookieJar;
-use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode, jwk::JwkSet};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 use crate::{
+    adapter::{IdTokenClaims, Keyring, Totp, generate_recovery_code},
     config,
-    entity::{error::AppError, user},
+    entity::{
+        api_key, error::AppError, login_attempt, one_time_token, refresh_token, revoked_token,
+        session, user,
+    },
+    repository::Repository,
 };
 
-#[derive(Debug, Clone, Copy)]
-pub struct Auth {}
+/// Verified against when a login names an unknown email, so it takes as long as a wrong
+/// password and the timing doesn't reveal which accounts exist.
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    let salt = SaltString::generate(&mut OsRng);
+    argon2()
+        .and_then(|argon2| {
+            let hash = argon2.hash_password(b"dummy password", &salt);
+            hash.map_err(password_hash_error)
+        })
+        .expect("hashing the dummy password")
+        .to_string()
+});
+
+#[derive(Clone)]
+pub struct Auth {
+    keyring: Keyring,
+    repo: Arc<dyn Repository>,
+}
 
 impl Auth {
-    pub fn new() -> Arc<Self> {
-        Arc::new(Auth {})
-    }
+    /// Panics if the configured keys can't be loaded or the Argon2 parameters are invalid,
+    /// since no token could be issued or no password checked.
+    pub fn new(repo: Arc<dyn Repository>) -> Arc<Self> {
+        if let Err(err) = argon2() {
+            panic!("{err}");
+        }
 
-    pub fn extract_user_from_actix(&self, req: HttpRequest) -> Result<user::Model, AppError> {
-        let result = req.cookie(config::ACCESS_TOKEN_COOKIE_NAME);
-        if result.is_none() {
-            let msg = format!("Cookie '{}' not found", config::ACCESS_TOKEN_COOKIE_NAME);
-            return Err(AppError::Unauthorized(msg));
+        match Keyring::from_config() {
+            Ok(keyring) => Auth::with_keyring(repo, keyring),
+            Err(err) => panic!("{err}"),
         }
+    }
 
-        return self.verify_access_token(result.unwrap().value());
+    pub fn with_keyring(repo: Arc<dyn Repository>, keyring: Keyring) -> Arc<Self> {
+        Arc::new(Auth { keyring, repo })
     }
 
-    pub fn extract_user_from_axum(&self, jar: CookieJar) -> Result<user::Model, AppError> {
-        let result = jar.get(config::ACCESS_TOKEN_COOKIE_NAME);
-        if result.is_none() {
-            let msg = format!("Cookie '{}' not found", config::ACCESS_TOKEN_COOKIE_NAME);
-            return Err(AppError::Unauthorized(msg));
-        }
+    pub fn extract_token_from_actix(&self, req: &HttpRequest) -> Result<String, AppError> {
+        self.extract_optional_token_from_actix(req)?
+            .ok_or_else(missing_token)
+    }
+
+    pub fn extract_token_from_axum(&self, headers: &HeaderMap) -> Result<String, AppError> {
+        self.extract_optional_token_from_axum(headers)?
+            .ok_or_else(missing_token)
+    }
+
+    /// Like `extract_token_from_actix`, but `None` when the request carries no credentials.
+    /// A malformed one is still an error.
+    pub fn extract_optional_token_from_actix(
+        &self,
+        req: &HttpRequest,
+    ) -> Result<Option<String>, AppError> {
+        let cookie = req
+            .cookie(config::ACCESS_TOKEN_COOKIE_NAME)
+            .map(|c| c.value().to_string());
+        let header = req
+            .headers()
+            .get(actix_web::http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        select_token(cookie, header)
+    }
 
-        return self.verify_access_token(result.unwrap().value());
+    /// Like `extract_token_from_axum`, but `None` when the request carries no credentials.
+    /// A malformed one is still an error.
+    pub fn extract_optional_token_from_axum(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<Option<String>, AppError> {
+        let cookie = CookieJar::from_headers(headers)
+            .get(config::ACCESS_TOKEN_COOKIE_NAME)
+            .map(|c| c.value().to_string());
+        let header = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        select_token(cookie, header)
     }
 
-    pub fn generate_access_token(&self, user: &user::Model) -> Result<String, AppError> {
-        let mut header = Header::new(Algorithm::HS256);
-        header.kid = Some("primary".to_string());
+    pub async fn extract_user_from_actix(&self, req: HttpRequest) -> Result<user::Model, AppError> {
+        let token = self.extract_token_from_actix(&req)?;
+        self.verify_token(&token, req.method().is_safe()).await
+    }
+
+    pub async fn extract_user_from_axum(
+        &self,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<user::Model, AppError> {
+        let token = self.extract_token_from_axum(headers)?;
+        self.verify_token(&token, method.is_safe()).await
+    }
+
+    /// Issues a personal API key for `user` and persists its hash. The key itself is only
+    /// returned here, it can't be recovered later.
+    pub async fn generate_api_key(
+        &self,
+        user: &user::Model,
+        name: String,
+        scope: api_key::Scope,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(String, api_key::Model), AppError> {
+        let key = format!("{}{}", config::API_KEY_PREFIX, random_token());
+        let api_key = api_key::Model {
+            expires_at,
+            key_hash: hash_token(&key),
+            name,
+            prefix: key[..config::API_KEY_PREFIX.len() + 8].to_string(),
+            scope,
+            user_id: user.id,
+            ..Default::default()
+        };
+        let api_key = self.repo.create_api_key(api_key).await?;
+        Ok((key, api_key))
+    }
+
+    /// Starts a session for a login, returning its access and refresh tokens.
+    pub async fn start_session(
+        &self,
+        user: &user::Model,
+        ip: Option<IpAddr>,
+        user_agent: String,
+    ) -> Result<(String, String), AppError> {
+        self.repo.delete_expired_sessions().await?;
+        let session = session::Model {
+            expires_at: Utc::now() + Duration::seconds(config::REFRESH_TOKEN_TTL_IN_SECONDS),
+            ip: ip.map(|ip| ip.to_string()),
+            user_agent,
+            user_id: user.id,
+            ..Default::default()
+        };
+        let session = self.repo.create_session(session).await?;
+        let access_token = self.generate_access_token(user, &session)?;
+        let refresh_token = self.generate_refresh_token(user, session.id).await?;
+        Ok((access_token, refresh_token))
+    }
+
+    /// Signs a session out: its refresh tokens are revoked and its access tokens are
+    /// rejected from now on. Returns `AppError::NotFound` unless it belongs to `user_id`.
+    pub async fn revoke_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.repo.delete_session(id, user_id).await?;
+        self.repo.revoke_refresh_token_family(id).await
+    }
+
+    /// Signs out every session of the user.
+    pub async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<(), AppError> {
+        self.repo.delete_user_sessions(user_id).await?;
+        self.repo.revoke_user_refresh_tokens(user_id).await
+    }
+
+    /// Signs the access token's `jti` with the session it belongs to.
+    pub fn generate_access_token(
+        &self,
+        user: &user::Model,
+        session: &session::Model,
+    ) -> Result<String, AppError> {
+        let key = self.keyring.signing_key()?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
 
         let now = Utc::now();
         let claims = Claims {
@@ -54,15 +195,15 @@ impl Auth {
             exp: (now + Duration::seconds(config::ACCESS_TOKEN_TTL_IN_SECONDS)).timestamp(),
             iat: now.timestamp(),
             iss: "api".to_string(),
-            jti: Uuid::now_v7(),
+            jti: session.jti,
+            sid: Some(session.id),
             sub: user.id,
             email: user.email.clone(),
             name: user.name.clone(),
+            role: user.role,
         };
 
-        let secret = config::access_token_secret();
-        let key = EncodingKey::from_secret(secret.as_bytes());
-        match encode(&header, &claims, &key) {
+        match encode(&header, &claims, &key.encoding) {
             Ok(token) => Ok(token),
             Err(err) => Err(AppError::Internal {
                 err: err.to_string(),
@@ -71,39 +212,390 @@ impl Auth {
         }
     }
 
-    pub fn verify_access_token(&self, token: &str) -> Result<user::Model, AppError> {
-        let secret = config::access_token_secret();
-        let key = DecodingKey::from_secret(secret.as_bytes());
+    /// Issues an opaque refresh token for `user` and persists its hash. Every token of a
+    /// session shares the session's id as its `family_id`.
+    pub async fn generate_refresh_token(
+        &self,
+        user: &user::Model,
+        family_id: Uuid,
+    ) -> Result<String, AppError> {
+        let token = random_token();
+        let refresh_token = refresh_token::Model {
+            expires_at: Utc::now() + Duration::seconds(config::REFRESH_TOKEN_TTL_IN_SECONDS),
+            family_id,
+            token_hash: hash_token(&token),
+            user_id: user.id,
+            ..Default::default()
+        };
+        self.repo.create_refresh_token(refresh_token).await?;
+        Ok(token)
+    }
 
-        let mut validation = Validation::new(Algorithm::HS256);
-        validation.set_audience(&["web"]);
-        validation.set_issuer(&["api"]);
-        validation.set_required_spec_claims(&["aud", "exp", "iss", "sub"]);
-        match decode::<Claims>(token, &key, &validation) {
-            Ok(data) => Ok(user::Model {
-                id: data.claims.sub,
-                email: data.claims.email.clone(),
-                name: data.claims.name.clone(),
-                ..Default::default()
-            }),
-            Err(err) => Err(AppError::Unauthorized(err.to_string())),
+    /// Issues a single-use token for `purpose` that expires after `ttl_in_seconds`, and
+    /// persists its hash.
+    pub async fn generate_one_time_token(
+        &self,
+        user: &user::Model,
+        purpose: one_time_token::Purpose,
+        ttl_in_seconds: i64,
+    ) -> Result<String, AppError> {
+        let token = random_token();
+        self.store_one_time_token(user, purpose, &token, ttl_in_seconds)
+            .await?;
+        Ok(token)
+    }
+
+    /// Issues a fresh set of recovery codes, invalidating the previous ones.
+    pub async fn generate_recovery_codes(
+        &self,
+        user: &user::Model,
+    ) -> Result<Vec<String>, AppError> {
+        let purpose = one_time_token::Purpose::MfaRecovery;
+        self.repo.delete_one_time_tokens(user.id, purpose).await?;
+
+        let ttl = config::MFA_RECOVERY_CODE_TTL_IN_SECONDS;
+        let mut codes = vec![];
+        for _ in 0..config::MFA_RECOVERY_CODE_COUNT {
+            let code = generate_recovery_code();
+            self.store_one_time_token(user, purpose, &code, ttl).await?;
+            codes.push(code);
+        }
+
+        Ok(codes)
+    }
+
+    /// Returns an unused, unexpired token issued for `purpose` without using it up.
+    pub async fn find_one_time_token(
+        &self,
+        token: &str,
+        purpose: one_time_token::Purpose,
+    ) -> Result<one_time_token::Model, AppError> {
+        let invalid = || AppError::Unauthorized("Token is invalid or has expired".to_string());
+        let one_time_token = match self
+            .repo
+            .get_one_time_token_by_hash(hash_token(token))
+            .await
+        {
+            Ok(one_time_token) => one_time_token,
+            Err(AppError::NotFound(_)) => return Err(invalid()),
+            Err(err) => return Err(err),
+        };
+
+        if one_time_token.purpose != purpose
+            || one_time_token.expires_at <= Utc::now()
+            || one_time_token.used_at.is_some()
+        {
+            return Err(invalid());
+        }
+
+        Ok(one_time_token)
+    }
+
+    /// Redeems a token issued for `purpose`, returning the id of the user it was issued to.
+    pub async fn redeem_one_time_token(
+        &self,
+        token: &str,
+        purpose: one_time_token::Purpose,
+    ) -> Result<Uuid, AppError> {
+        let one_time_token = self.find_one_time_token(token, purpose).await?;
+        if !self
+            .repo
+            .mark_one_time_token_used(one_time_token.id)
+            .await?
+        {
+            return Err(AppError::Unauthorized(
+                "Token is invalid or has expired".to_string(),
+            ));
+        }
+
+        Ok(one_time_token.user_id)
+    }
+
+    /// Accepts a TOTP code from the user's authenticator that wasn't used before or, once
+    /// MFA is enabled, one of their unused recovery codes.
+    pub async fn verify_mfa_code(&self, user: &user::Model, code: &str) -> Result<(), AppError> {
+        let invalid = || AppError::Unauthorized("MFA code is invalid".to_string());
+        let Some(secret) = &user.mfa_secret else {
+            return Err(invalid());
+        };
+
+        if let Some(step) = Totp::new(secret, &user.email)?.verify(code) {
+            return match self.repo.mark_user_mfa_step_used(user.id, step).await? {
+                true => Ok(()),
+                false => Err(invalid()),
+            };
+        }
+
+        if user.mfa_enabled_at.is_none() {
+            return Err(invalid());
+        }
+
+        let purpose = one_time_token::Purpose::MfaRecovery;
+        let recovery_code = match self.find_one_time_token(code, purpose).await {
+            Ok(recovery_code) => recovery_code,
+            Err(AppError::Unauthorized(_)) => return Err(invalid()),
+            Err(err) => return Err(err),
+        };
+        if recovery_code.user_id != user.id
+            || !self.repo.mark_one_time_token_used(recovery_code.id).await?
+        {
+            return Err(invalid());
+        }
+
+        Ok(())
+    }
+
+    /// Checks a code for the user of an MFA challenge, returning the user and using up
+    /// the challenge if it is valid. Wrong codes count as failed logins of that user and,
+    /// once there are `MFA_MAX_FAILURES` of them, burn the challenge and lock the user out
+    /// of challenges with `AppError::TooManyRequests`.
+    pub async fn verify_mfa_challenge(
+        &self,
+        mfa_token: &str,
+        code: &str,
+    ) -> Result<user::Model, AppError> {
+        let purpose = one_time_token::Purpose::MfaChallenge;
+        let challenge = self.find_one_time_token(mfa_token, purpose).await?;
+        let key = login_attempt::Model::mfa_key(challenge.user_id);
+        let now = Utc::now();
+        if let Some(attempt) = self.repo.get_login_attempt(key.clone()).await?
+            && let Some(locked_until) = attempt.locked_until(config::MFA_MAX_FAILURES)
+            && locked_until > now
+        {
+            self.repo.mark_one_time_token_used(challenge.id).await?;
+            return Err(locked_out(locked_until, now));
+        }
+
+        let user = self.repo.get_user_by_id(challenge.user_id).await?;
+        match self.verify_mfa_code(&user, code).await {
+            Ok(()) => {}
+            Err(AppError::Unauthorized(msg)) => {
+                let expires_at = now + Duration::seconds(config::LOGIN_ATTEMPT_TTL_IN_SECONDS);
+                let attempt = self.repo.record_failed_login(key, expires_at).await?;
+                if let Some(locked_until) = attempt.locked_until(config::MFA_MAX_FAILURES) {
+                    self.repo.mark_one_time_token_used(challenge.id).await?;
+                    return Err(locked_out(locked_until, now));
+                }
+                return Err(AppError::Unauthorized(msg));
+            }
+            Err(err) => return Err(err),
+        }
+
+        self.redeem_one_time_token(mfa_token, purpose).await?;
+        self.repo.delete_login_attempt(key).await?;
+        Ok(user)
+    }
+
+    /// Exchanges a refresh token for new access and refresh tokens of the same session,
+    /// recording where the session was seen. Presenting a token that was already
+    /// exchanged signs the session out, since either the client or an attacker holds a
+    /// stolen copy.
+    pub async fn rotate_refresh_token(
+        &self,
+        token: &str,
+        ip: Option<IpAddr>,
+        user_agent: String,
+    ) -> Result<(user::Model, (String, String)), AppError> {
+        let refresh_token = match self.repo.get_refresh_token_by_hash(hash_token(token)).await {
+            Ok(refresh_token) => refresh_token,
+            Err(AppError::NotFound(_)) => {
+                return Err(AppError::Unauthorized(
+                    "Refresh token is invalid".to_string(),
+                ));
+            }
+            Err(err) => return Err(err),
+        };
+
+        if refresh_token.revoked_at.is_some() {
+            return Err(AppError::Unauthorized(
+                "Refresh token has been revoked".to_string(),
+            ));
+        }
+
+        if refresh_token.expires_at <= Utc::now() {
+            return Err(AppError::Unauthorized(
+                "Refresh token has expired".to_string(),
+            ));
+        }
+
+        if !self.repo.mark_refresh_token_used(refresh_token.id).await? {
+            log::warn!(
+                "Refresh token reuse detected, revoking family '{}'",
+                refresh_token.family_id
+            );
+            self.end_session(refresh_token.family_id, refresh_token.user_id)
+                .await?;
+            return Err(AppError::Unauthorized(
+                "Refresh token has already been used".to_string(),
+            ));
+        }
+
+        let user = match self.repo.get_user_by_id(refresh_token.user_id).await {
+            Ok(user) => user,
+            Err(AppError::NotFound(_)) => {
+                return Err(AppError::Unauthorized("User no longer exists".to_string()));
+            }
+            Err(err) => return Err(err),
+        };
+        // Families from before sessions were recorded have none to refresh.
+        let mut session = match self.repo.get_session_by_id(refresh_token.family_id).await {
+            Ok(session) => session,
+            Err(AppError::NotFound(_)) => {
+                self.end_session(refresh_token.family_id, user.id).await?;
+                return Err(AppError::Unauthorized(
+                    "Session has been revoked".to_string(),
+                ));
+            }
+            Err(err) => return Err(err),
+        };
+        let now = Utc::now();
+        session.expires_at = now + Duration::seconds(config::REFRESH_TOKEN_TTL_IN_SECONDS);
+        session.ip = ip.map(|ip| ip.to_string());
+        session.jti = Uuid::now_v7();
+        session.last_seen_at = now;
+        session.user_agent = user_agent;
+        self.repo.update_session(session.clone()).await?;
+
+        let access_token = self.generate_access_token(&user, &session)?;
+        let refresh_token = self.generate_refresh_token(&user, session.id).await?;
+        Ok((user, (access_token, refresh_token)))
+    }
+
+    /// Adds the token's `jti` to the denylist until the token expires, purging entries
+    /// for tokens that have expired since.
+    pub async fn revoke_access_token(&self, token: &str) -> Result<(), AppError> {
+        // API keys stay valid until they are revoked on their own.
+        if token.starts_with(config::API_KEY_PREFIX) {
+            return Ok(());
+        }
+
+        let claims = self.decode_access_token(token)?;
+        if let Some(sid) = claims.sid {
+            self.end_session(sid, claims.sub).await?;
+        }
+        self.repo.delete_expired_revoked_tokens().await?;
+
+        let revoked_token = revoked_token::Model {
+            id: claims.jti,
+            expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now),
+        };
+        self.repo.create_revoked_token(revoked_token).await
+    }
+
+    pub async fn revoke_refresh_token(&self, token: &str) -> Result<(), AppError> {
+        match self.repo.get_refresh_token_by_hash(hash_token(token)).await {
+            Ok(refresh_token) => {
+                self.end_session(refresh_token.family_id, refresh_token.user_id)
+                    .await
+            }
+            Err(AppError::NotFound(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolves an API key to its owner. Read-only keys are refused unless `safe_method`,
+    /// i.e. the request is a `GET`, `HEAD` or `OPTIONS`.
+    pub async fn verify_api_key(
+        &self,
+        key: &str,
+        safe_method: bool,
+    ) -> Result<user::Model, AppError> {
+        let api_key = match self.repo.get_api_key_by_hash(hash_token(key)).await {
+            Ok(api_key) => api_key,
+            Err(AppError::NotFound(_)) => {
+                return Err(AppError::Unauthorized("API key is invalid".to_string()));
+            }
+            Err(err) => return Err(err),
+        };
+        if api_key
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(AppError::Unauthorized("API key has expired".to_string()));
         }
+        if api_key.scope == api_key::Scope::ReadOnly && !safe_method {
+            return Err(AppError::Forbidden("API key is read-only".to_string()));
+        }
+
+        let user = self.repo.get_user_by_id(api_key.user_id).await?;
+        Ok(user.set_password("".to_string()))
+    }
+
+    /// Verifies whichever credential `token` is, an API key or an access token.
+    pub async fn verify_token(
+        &self,
+        token: &str,
+        safe_method: bool,
+    ) -> Result<user::Model, AppError> {
+        if token.starts_with(config::API_KEY_PREFIX) {
+            return self.verify_api_key(token, safe_method).await;
+        }
+
+        self.verify_access_token(token).await
+    }
+
+    pub async fn verify_access_token(&self, token: &str) -> Result<user::Model, AppError> {
+        let claims = self.decode_access_token(token)?;
+        if self.repo.is_token_revoked(claims.jti).await? {
+            return Err(AppError::Unauthorized(
+                "Access token has been revoked".to_string(),
+            ));
+        }
+        if let Some(sid) = claims.sid {
+            self.verify_session(sid).await?;
+        }
+
+        // Read from the user rather than the claims, so a role change applies right away.
+        let user = match self.repo.get_user_by_id(claims.sub).await {
+            Ok(user) => user,
+            Err(AppError::NotFound(_)) => {
+                return Err(AppError::Unauthorized("User no longer exists".to_string()));
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(user::Model {
+            id: user.id,
+            email: user.email,
+            name: user.name,
+            role: user.role,
+            ..Default::default()
+        })
     }
 
     pub fn hash_password(&self, password: &str) -> Result<String, AppError> {
         let salt = SaltString::generate(&mut OsRng);
-        match Argon2::default().hash_password(password.as_bytes(), &salt) {
+        match argon2()?.hash_password(password.as_bytes(), &salt) {
             Ok(hash) => Ok(hash.to_string()),
-            Err(err) => Err(AppError::Internal {
-                err: err.to_string(),
-                path: format!("{}:{}:{}", file!(), line!(), column!()),
-            }),
+            Err(err) => Err(password_hash_error(err)),
         }
     }
 
+    /// Whether `password_hash` was made with a weaker algorithm or lower costs than new
+    /// hashes get, so it should be replaced the next time the password is known.
+    pub fn password_needs_rehash(&self, password_hash: &str) -> Result<bool, AppError> {
+        let hash = PasswordHash::new(password_hash).map_err(password_hash_error)?;
+        let params = Params::try_from(&hash).map_err(password_hash_error)?;
+        let argon2 = argon2()?;
+        let current = argon2.params();
+        Ok(hash.algorithm != Algorithm::Argon2id.ident()
+            || hash
+                .version
+                .is_none_or(|version| version < Version::V0x13.into())
+            || params.m_cost() < current.m_cost()
+            || params.t_cost() < current.t_cost()
+            || params.p_cost() < current.p_cost())
+    }
+
+    /// Checks `password` against the costs recorded in `password_hash`, not the configured
+    /// ones, so hashes made before a change still verify.
     pub fn verify_password(&self, password: &str, password_hash: &str) -> Result<(), AppError> {
+        if password_hash.is_empty() {
+            return Err(AppError::Unauthorized(
+                "Account has no password".to_string(),
+            ));
+        }
         match PasswordHash::new(password_hash) {
-            Ok(hash) => match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(hash) => match argon2()?.verify_password(password.as_bytes(), &hash) {
                 Ok(_) => Ok(()),
                 Err(err) => Err(AppError::Unauthorized(err.to_string())),
             },
@@ -113,6 +605,337 @@ impl Auth {
             }),
         }
     }
+
+    /// Confirms a sensitive change with the user's password, counting wrong ones like
+    /// failed logins so a stolen token can't be used to guess it. Accounts without one,
+    /// which only sign in through OIDC, confirm it with a session `token` that signed in
+    /// within `REAUTHENTICATION_WINDOW_IN_SECONDS`.
+    pub async fn confirm_identity(
+        &self,
+        user: &user::Model,
+        password: Option<&str>,
+        token: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), AppError> {
+        if !user.password.is_empty() {
+            let Some(password) = password else {
+                return Err(AppError::Unauthorized("Password is required".to_string()));
+            };
+            self.authenticate(&user.email, password, ip).await?;
+            return Ok(());
+        }
+
+        let reauthenticate = AppError::Unauthorized("Sign in again to confirm".to_string());
+        // API keys don't belong to a sign-in.
+        if token.starts_with(config::API_KEY_PREFIX) {
+            return Err(reauthenticate);
+        }
+        let Some(sid) = self.decode_access_token(token)?.sid else {
+            return Err(reauthenticate);
+        };
+        let session = self.repo.get_session_by_id(sid).await?;
+        let window = Duration::seconds(config::REAUTHENTICATION_WINDOW_IN_SECONDS);
+        if Utc::now() - session.created_at >= window {
+            return Err(reauthenticate);
+        }
+        Ok(())
+    }
+
+    /// Checks a login's credentials, counting failures against the account and `ip`, and
+    /// rejects it with `AppError::TooManyRequests` while either is locked out. An unknown
+    /// email gets the same answer as a wrong password.
+    pub async fn authenticate(
+        &self,
+        email: &str,
+        password: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<user::Model, AppError> {
+        let account_key = login_attempt::Model::email_key(email);
+        let mut keys = vec![(
+            account_key.clone(),
+            config::login_max_failures_per_account(),
+        )];
+        if let Some(ip) = ip {
+            keys.push((
+                login_attempt::Model::ip_key(ip),
+                config::login_max_failures_per_ip(),
+            ));
+        }
+
+        let now = Utc::now();
+        for (key, max_failures) in &keys {
+            let Some(attempt) = self.repo.get_login_attempt(key.clone()).await? else {
+                continue;
+            };
+            if let Some(locked_until) = attempt.locked_until(*max_failures)
+                && locked_until > now
+            {
+                return Err(locked_out(locked_until, now));
+            }
+        }
+
+        let user = match self.repo.get_user_by_email(email.to_string()).await {
+            Ok(user) => Some(user),
+            Err(AppError::NotFound(_)) => None,
+            Err(err) => return Err(err),
+        };
+        // Users that only sign in through OIDC have no password to check.
+        let password_hash = match &user {
+            Some(user) if !user.password.is_empty() => Some(user.password.as_str()),
+            _ => None,
+        };
+        let verified =
+            match self.verify_password(password, password_hash.unwrap_or(&DUMMY_PASSWORD_HASH)) {
+                Ok(()) => password_hash.is_some(),
+                Err(AppError::Unauthorized(_)) => false,
+                Err(err) => return Err(err),
+            };
+
+        match user {
+            Some(user) if verified => {
+                self.repo.delete_login_attempt(account_key).await?;
+                self.rehash_password(&user, password).await;
+                Ok(user)
+            }
+            _ => {
+                let expires_at = now + Duration::seconds(config::LOGIN_ATTEMPT_TTL_IN_SECONDS);
+                for (key, _) in keys {
+                    self.repo.record_failed_login(key, expires_at).await?;
+                }
+                Err(AppError::Unauthorized(
+                    "Email or password is incorrect".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Finds the account for an OIDC login by the issuer's verified email, creating one
+    /// without a password if there is none. A matching account's email counts as verified
+    /// from then on.
+    pub async fn find_or_create_oidc_user(
+        &self,
+        claims: &IdTokenClaims,
+    ) -> Result<user::Model, AppError> {
+        if claims.email.is_empty() || !claims.email_verified {
+            return Err(AppError::Forbidden(
+                "Email is not verified by the identity provider".to_string(),
+            ));
+        }
+
+        match self.repo.get_user_by_email(claims.email.clone()).await {
+            Ok(mut user) => {
+                if user.email_verified_at.is_none() {
+                    self.repo.verify_user_email(user.id).await?;
+                    user.email_verified_at = Some(Utc::now());
+                }
+                Ok(user)
+            }
+            Err(AppError::NotFound(_)) => {
+                let name = match claims.name.is_empty() {
+                    true => claims.email.split('@').next().unwrap_or_default(),
+                    false => claims.name.as_str(),
+                };
+                let user = user::Model {
+                    email: claims.email.clone(),
+                    email_verified_at: Some(Utc::now()),
+                    name: name.to_string(),
+                    ..Default::default()
+                };
+                self.repo.create_user(user).await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        self.keyring.jwks()
+    }
+
+    /// Verifies the token with the key named by its `kid`. The key's algorithm is the only
+    /// one accepted, so a token can't pick a weaker one.
+    fn decode_access_token(&self, token: &str) -> Result<Claims, AppError> {
+        let kid = match decode_header(token) {
+            Ok(header) => header.kid.unwrap_or_default(),
+            Err(err) => return Err(AppError::Unauthorized(err.to_string())),
+        };
+        let Some(key) = self.keyring.get(&kid) else {
+            return Err(AppError::Unauthorized(format!(
+                "Access token key '{kid}' is unknown"
+            )));
+        };
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.set_audience(&["web"]);
+        validation.set_issuer(&["api"]);
+        validation.set_required_spec_claims(&["aud", "exp", "iss", "sub"]);
+        match decode::<Claims>(token, &key.decoding, &validation) {
+            Ok(data) => Ok(data.claims),
+            Err(err) => Err(AppError::Unauthorized(err.to_string())),
+        }
+    }
+
+    /// Like `revoke_session`, for callers that already know whose session it is. The
+    /// refresh tokens are revoked even if the session is gone.
+    async fn end_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.repo.revoke_refresh_token_family(id).await?;
+        match self.repo.delete_session(id, user_id).await {
+            Ok(()) | Err(AppError::NotFound(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Rejects tokens of a session that was signed out, and keeps its `last_seen_at`
+    /// roughly up to date.
+    async fn verify_session(&self, id: Uuid) -> Result<(), AppError> {
+        let now = Utc::now();
+        let session = match self.repo.get_session_by_id(id).await {
+            Ok(session) if session.expires_at > now => session,
+            Ok(_) | Err(AppError::NotFound(_)) => {
+                return Err(AppError::Unauthorized(
+                    "Session has been revoked".to_string(),
+                ));
+            }
+            Err(err) => return Err(err),
+        };
+
+        let interval = Duration::seconds(config::SESSION_LAST_SEEN_INTERVAL_IN_SECONDS);
+        if now - session.last_seen_at >= interval {
+            self.repo.update_session_last_seen(id, now).await?;
+        }
+        Ok(())
+    }
+
+    /// Upgrades the user's hash to the configured parameters if it is weaker. Failing to
+    /// is only logged, the login itself already succeeded.
+    async fn rehash_password(&self, user: &user::Model, password: &str) {
+        let result = match self.password_needs_rehash(&user.password) {
+            Ok(true) => match self.hash_password(password) {
+                Ok(hash) => self.repo.update_user_password(user.id, hash).await,
+                Err(err) => Err(err),
+            },
+            Ok(false) => Ok(()),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            log::error!("Failed to rehash the password of user '{}': {err}", user.id);
+        }
+    }
+
+    async fn store_one_time_token(
+        &self,
+        user: &user::Model,
+        purpose: one_time_token::Purpose,
+        token: &str,
+        ttl_in_seconds: i64,
+    ) -> Result<(), AppError> {
+        let one_time_token = one_time_token::Model {
+            expires_at: Utc::now() + Duration::seconds(ttl_in_seconds),
+            purpose,
+            token_hash: hash_token(token),
+            user_id: user.id,
+            ..Default::default()
+        };
+        self.repo.create_one_time_token(one_time_token).await?;
+        Ok(())
+    }
+}
+
+pub(super) fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Picks the access token from the first configured source that carries one. A present
+/// but malformed `Authorization` header still counts, so it can't silently fall through
+/// to a lower precedence source.
+///
+/// API keys are always taken from the header, whatever the configured sources, since
+/// scripts have no other way to send them.
+/// The token of the first configured source the request has one in, `None` if none has.
+fn select_token(cookie: Option<String>, header: Option<&str>) -> Result<Option<String>, AppError> {
+    if let Some((scheme, token)) = header.and_then(|value| value.split_once(' '))
+        && scheme.eq_ignore_ascii_case("Bearer")
+        && token.trim().starts_with(config::API_KEY_PREFIX)
+    {
+        return Ok(Some(token.trim().to_string()));
+    }
+
+    for source in config::auth_token_sources().split(',').map(str::trim) {
+        match source {
+            "cookie" => {
+                if let Some(token) = cookie.clone() {
+                    return Ok(Some(token));
+                }
+            }
+            "header" => {
+                if let Some(value) = header {
+                    return match value.split_once(' ') {
+                        Some((scheme, token)) if scheme.eq_ignore_ascii_case("Bearer") => {
+                            Ok(Some(token.trim().to_string()))
+                        }
+                        _ => Err(AppError::Unauthorized(
+                            "Authorization header must use the Bearer scheme".to_string(),
+                        )),
+                    };
+                }
+            }
+            other => log::warn!("Ignoring unknown auth token source '{other}'"),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Rejects a request without credentials, naming the places they were looked for.
+fn missing_token() -> AppError {
+    let searched: Vec<String> = config::auth_token_sources()
+        .split(',')
+        .map(str::trim)
+        .filter_map(|source| match source {
+            "cookie" => Some(format!("Cookie '{}'", config::ACCESS_TOKEN_COOKIE_NAME)),
+            "header" => Some("Authorization header".to_string()),
+            _ => None,
+        })
+        .collect();
+    AppError::Unauthorized(format!("{} not found", searched.join(" or ")))
+}
+
+/// Argon2id with the configured costs. They only apply to new hashes, verification uses
+/// the ones recorded in the hash.
+fn argon2() -> Result<Argon2<'static>, AppError> {
+    let params = Params::new(
+        config::argon2_memory_cost(),
+        config::argon2_time_cost(),
+        config::argon2_parallelism(),
+        None,
+    );
+    match params {
+        Ok(params) => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        Err(err) => Err(AppError::Internal {
+            err: format!("Invalid Argon2 parameters: {err}"),
+            path: format!("{}:{}:{}", file!(), line!(), column!()),
+        }),
+    }
+}
+
+/// The answer to logins and MFA codes while a lockout lasts.
+fn locked_out(locked_until: DateTime<Utc>, now: DateTime<Utc>) -> AppError {
+    AppError::TooManyRequests {
+        msg: "Too many failed logins, try again later".to_string(),
+        retry_after: (locked_until - now).num_seconds().max(1),
+    }
+}
+
+fn password_hash_error(err: impl ToString) -> AppError {
+    AppError::Internal {
+        err: err.to_string(),
+        path: format!("{}:{}:{}", file!(), line!(), column!()),
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -122,7 +945,12 @@ struct Claims {
     iat: i64,
     iss: String,
     jti: Uuid,
+    /// The session the token was issued to, absent from tokens that predate sessions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<Uuid>,
     sub: Uuid,
     email: String,
     name: String,
+    #[serde(default)]
+    role: user::Role,
 }
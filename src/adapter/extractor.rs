@@ -0,0 +1,186 @@
+use std::{
+    future::{Future, Ready, ready},
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+
+use crate::{
+    AppState, config,
+    entity::{error::AppError, user},
+};
+
+/// The user identified by the request's access token. Handlers that take it reject
+/// unauthenticated requests with 401 before their body runs.
+#[derive(Clone, Debug)]
+pub struct AuthUser(pub user::Model);
+
+/// Like `AuthUser`, but yields `None` when the request carries no credentials. A token
+/// that is present but invalid is still rejected.
+#[derive(Clone, Debug)]
+pub struct OptionalAuthUser(pub Option<user::Model>);
+
+/// The address of the client, taken from `X-Forwarded-For` when
+/// `config::trust_forwarded_for` is set. `None` when it can't be told, e.g. in tests.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+/// The request's `User-Agent` header, empty without one.
+#[derive(Clone, Debug)]
+pub struct UserAgent(pub String);
+
+impl FromRequestParts<Arc<AppState>> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let forwarded_for = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok());
+        Ok(ClientIp(client_ip(peer, forwarded_for)))
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for UserAgent {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Ok(UserAgent(user_agent.to_string()))
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = state
+            .auth
+            .extract_user_from_axum(&parts.method, &parts.headers)
+            .await?;
+        Ok(AuthUser(user))
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for OptionalAuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(token) = state
+            .auth
+            .extract_optional_token_from_axum(&parts.headers)?
+        else {
+            return Ok(OptionalAuthUser(None));
+        };
+        let safe_method = parts.method.is_safe();
+        let user = state.auth.verify_token(&token, safe_method).await?;
+        Ok(OptionalAuthUser(Some(user)))
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let auth = actix_state(&req)?.auth.clone();
+            let user = auth.extract_user_from_actix(req).await?;
+            Ok(AuthUser(user))
+        })
+    }
+}
+
+impl FromRequest for OptionalAuthUser {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let auth = actix_state(&req)?.auth.clone();
+            let Some(token) = auth.extract_optional_token_from_actix(&req)? else {
+                return Ok(OptionalAuthUser(None));
+            };
+            let user = auth.verify_token(&token, req.method().is_safe()).await?;
+            Ok(OptionalAuthUser(Some(user)))
+        })
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        let forwarded_for = req
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok());
+        ready(Ok(ClientIp(client_ip(peer, forwarded_for))))
+    }
+}
+
+impl FromRequest for UserAgent {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let user_agent = req
+            .headers()
+            .get(actix_web::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        ready(Ok(UserAgent(user_agent.to_string())))
+    }
+}
+
+fn client_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+    if !config::trust_forwarded_for() {
+        return peer;
+    }
+
+    // The proxy appends the address it saw, anything before it came from the client.
+    forwarded_for
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|ip| ip.trim().parse().ok())
+        .or(peer)
+}
+
+fn actix_state(req: &HttpRequest) -> Result<&web::Data<AppState>, AppError> {
+    match req.app_data::<web::Data<AppState>>() {
+        Some(state) => Ok(state),
+        None => Err(AppError::Internal {
+            err: "AppState is not registered as app data".to_string(),
+            path: format!("{}:{}:{}", file!(), line!(), column!()),
+        }),
+    }
+}
@@ -0,0 +1,169 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::pkcs8::DecodePrivateKey as _;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use rsa::{
+    RsaPrivateKey, pkcs1::DecodeRsaPrivateKey, pkcs8::DecodePrivateKey as _, traits::PublicKeyParts,
+};
+
+use crate::{config, entity::error::AppError};
+
+/// A key access tokens are signed and verified with, identified by the token's `kid`.
+#[derive(Clone)]
+pub struct Key {
+    pub algorithm: Algorithm,
+    pub kid: String,
+    pub(super) decoding: DecodingKey,
+    pub(super) encoding: EncodingKey,
+    /// The public half, `None` for shared secrets which must never be published.
+    jwk: Option<Jwk>,
+}
+
+impl Key {
+    pub fn from_secret(kid: &str, secret: &[u8]) -> Self {
+        Key {
+            algorithm: Algorithm::HS256,
+            kid: kid.to_string(),
+            decoding: DecodingKey::from_secret(secret),
+            encoding: EncodingKey::from_secret(secret),
+            jwk: None,
+        }
+    }
+
+    /// Loads an RS256 (PKCS#1 or PKCS#8) or EdDSA (PKCS#8) private key.
+    pub fn from_pem(kid: &str, algorithm: Algorithm, pem: &str) -> Result<Self, AppError> {
+        let (encoding, params) = match algorithm {
+            Algorithm::RS256 => {
+                let key = RsaPrivateKey::from_pkcs8_pem(pem)
+                    .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+                    .map_err(|err| invalid_key(kid, err))?;
+                let params = AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                });
+                let encoding = EncodingKey::from_rsa_pem(pem.as_bytes());
+                (encoding, params)
+            }
+            Algorithm::EdDSA => {
+                let key = ed25519_dalek::SigningKey::from_pkcs8_pem(pem)
+                    .map_err(|err| invalid_key(kid, err))?;
+                let params = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(key.verifying_key().to_bytes()),
+                });
+                let encoding = EncodingKey::from_ed_pem(pem.as_bytes());
+                (encoding, params)
+            }
+            other => return Err(invalid_key(kid, format!("unsupported algorithm {other:?}"))),
+        };
+
+        let jwk = Jwk {
+            common: CommonParameters {
+                key_algorithm: Some(match algorithm {
+                    Algorithm::RS256 => KeyAlgorithm::RS256,
+                    _ => KeyAlgorithm::EdDSA,
+                }),
+                key_id: Some(kid.to_string()),
+                public_key_use: Some(PublicKeyUse::Signature),
+                ..Default::default()
+            },
+            algorithm: params,
+        };
+        Ok(Key {
+            algorithm,
+            kid: kid.to_string(),
+            decoding: DecodingKey::from_jwk(&jwk).map_err(|err| invalid_key(kid, err))?,
+            encoding: encoding.map_err(|err| invalid_key(kid, err))?,
+            jwk: Some(jwk),
+        })
+    }
+}
+
+/// Every key that access tokens may be verified with. The first key signs new tokens, so
+/// a key is rotated by putting its replacement first and keeping it listed until the
+/// tokens it signed have expired.
+#[derive(Clone)]
+pub struct Keyring {
+    keys: Vec<Key>,
+}
+
+impl Keyring {
+    pub fn new(keys: Vec<Key>) -> Self {
+        Keyring { keys }
+    }
+
+    /// Builds the keyring from `ACCESS_TOKEN_KEYS`, or a single HS256 key from
+    /// `ACCESS_TOKEN_SECRET` when it isn't set.
+    pub fn from_config() -> Result<Self, AppError> {
+        let spec = config::access_token_keys();
+        if spec.trim().is_empty() {
+            let secret = config::access_token_secret();
+            return Ok(Keyring::new(vec![Key::from_secret(
+                "primary",
+                secret.as_bytes(),
+            )]));
+        }
+
+        let mut keys = vec![];
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = entry.splitn(3, ':');
+            let (kid, algorithm, path) = (parts.next(), parts.next(), parts.next());
+            let key = match (kid, algorithm, path) {
+                (Some(kid), Some("HS256"), None) => {
+                    Key::from_secret(kid, config::access_token_secret().as_bytes())
+                }
+                (Some(kid), Some("HS256"), Some(path)) => {
+                    Key::from_secret(kid, read_key_file(kid, path)?.trim().as_bytes())
+                }
+                (Some(kid), Some("RS256"), Some(path)) => {
+                    Key::from_pem(kid, Algorithm::RS256, &read_key_file(kid, path)?)?
+                }
+                (Some(kid), Some("EdDSA"), Some(path)) => {
+                    Key::from_pem(kid, Algorithm::EdDSA, &read_key_file(kid, path)?)?
+                }
+                _ => return Err(invalid_key(entry, "expected 'kid:algorithm[:path]'")),
+            };
+            keys.push(key);
+        }
+
+        Ok(Keyring::new(keys))
+    }
+
+    pub fn get(&self, kid: &str) -> Option<&Key> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+
+    /// The public keys of every asymmetric key, for `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> JwkSet {
+        let keys = self.keys.iter().filter_map(|key| key.jwk.clone()).collect();
+        JwkSet { keys }
+    }
+
+    pub fn signing_key(&self) -> Result<&Key, AppError> {
+        match self.keys.first() {
+            Some(key) => Ok(key),
+            None => Err(AppError::Internal {
+                err: "Keyring has no signing key".to_string(),
+                path: format!("{}:{}:{}", file!(), line!(), column!()),
+            }),
+        }
+    }
+}
+
+fn invalid_key(kid: &str, err: impl ToString) -> AppError {
+    AppError::Internal {
+        err: format!("Invalid access token key '{kid}': {}", err.to_string()),
+        path: format!("{}:{}:{}", file!(), line!(), column!()),
+    }
+}
+
+fn read_key_file(kid: &str, path: &str) -> Result<String, AppError> {
+    std::fs::read_to_string(path).map_err(|err| invalid_key(kid, err))
+}
@@ -0,0 +1,104 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{config, entity::error::AppError};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Mail {
+    pub body: String,
+    pub subject: String,
+    pub to: String,
+}
+
+impl Mail {
+    pub fn email_verification(to: &str, token: &str) -> Self {
+        let hours = config::EMAIL_VERIFICATION_TOKEN_TTL_IN_SECONDS / 3600;
+        Mail {
+            body: format!(
+                "Use this token to verify your email, it expires in {hours} hours:\n\n{token}\n\nIf you didn't create an account, you can ignore this email."
+            ),
+            subject: "Verify your email".to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    pub fn password_reset(to: &str, token: &str) -> Self {
+        let minutes = config::PASSWORD_RESET_TOKEN_TTL_IN_SECONDS / 60;
+        Mail {
+            body: format!(
+                "Use this token to reset your password, it expires in {minutes} minutes:\n\n{token}\n\nIf you didn't ask for a reset, you can ignore this email."
+            ),
+            subject: "Reset your password".to_string(),
+            to: to.to_string(),
+        }
+    }
+}
+
+/// Delivers mail to users. Implementations for real providers plug in here, the ones
+/// below are meant for development and tests.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), AppError>;
+}
+
+/// Writes every mail to the log instead of sending it.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        log::info!(
+            "Mail to '{}' with subject '{}':\n{}",
+            mail.to,
+            mail.subject,
+            mail.body
+        );
+        Ok(())
+    }
+}
+
+impl LogMailer {
+    pub fn into_shared(self) -> Arc<dyn Mailer> {
+        Arc::new(self)
+    }
+}
+
+/// Writes every mail as a JSON file to `dir`, named so they sort in the order sent.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        let path = self.dir.join(format!("{}.json", Uuid::now_v7()));
+        let result = match tokio::fs::create_dir_all(&self.dir).await {
+            Ok(_) => tokio::fs::write(&path, serde_json::json!(mail).to_string()).await,
+            Err(err) => Err(err),
+        };
+        result.map_err(|err| AppError::Internal {
+            err: err.to_string(),
+            path: format!("{}:{}:{}", file!(), line!(), column!()),
+        })
+    }
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileMailer { dir: dir.into() }
+    }
+
+    pub fn into_shared(self) -> Arc<dyn Mailer> {
+        Arc::new(self)
+    }
+}
+
+pub fn new_mailer() -> Arc<dyn Mailer> {
+    match config::mailer_type().as_str() {
+        "file" => FileMailer::new(config::mailer_dir()).into_shared(),
+        _ => LogMailer.into_shared(),
+    }
+}
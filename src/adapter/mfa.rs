@@ -0,0 +1,54 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use totp_rs::{Builder, Secret};
+
+use crate::{config, entity::error::AppError};
+
+/// A user's TOTP (RFC 6238) authenticator, with the parameters every authenticator app
+/// assumes: SHA-1, 6 digits and 30 second steps. One step either side is accepted to
+/// allow for clock drift.
+pub struct Totp(totp_rs::Totp);
+
+impl Totp {
+    pub fn new(secret: &str, account: &str) -> Result<Self, AppError> {
+        let secret = Secret::try_from_base32(secret).map_err(invalid_totp)?;
+        let totp = Builder::new()
+            .with_secret(secret)
+            .with_account_name(account)
+            .with_issuer(Some(config::mfa_issuer()))
+            .build()
+            .map_err(invalid_totp)?;
+        Ok(Totp(totp))
+    }
+
+    /// A new base32 encoded secret.
+    pub fn generate_secret() -> String {
+        Secret::generate().to_base32()
+    }
+
+    /// The `otpauth://` URI authenticator apps enrol from, usually shown as a QR code.
+    pub fn uri(&self) -> Result<String, AppError> {
+        self.0.to_url().map_err(invalid_totp)
+    }
+
+    /// Returns the time step `code` belongs to. Callers must reject a step that was
+    /// already used so a code can't be replayed.
+    pub fn verify(&self, code: &str) -> Option<i64> {
+        let step = self.0.check_current(code)?;
+        i64::try_from(step).ok()
+    }
+}
+
+/// A recovery code like `4f0c1-9ab3e`, short enough to write down.
+pub fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; 5];
+    OsRng.fill_bytes(&mut bytes);
+    let code: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("{}-{}", &code[..5], &code[5..])
+}
+
+fn invalid_totp(err: impl ToString) -> AppError {
+    AppError::Internal {
+        err: format!("Invalid TOTP configuration: {}", err.to_string()),
+        path: format!("{}:{}:{}", file!(), line!(), column!()),
+    }
+}
@@ -1,3 +1,14 @@
 mod auth;
+mod extractor;
+mod keyring;
+mod mailer;
+mod mfa;
+mod oidc;
+pub mod policy;
 
-pub use auth::*;
\ No newline at end of file
+pub use auth::*;
+pub use extractor::*;
+pub use keyring::*;
+pub use mailer::*;
+pub use mfa::*;
+pub use oidc::*;
@@ -0,0 +1,293 @@
+use std::{sync::Arc, time::Duration};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use reqwest::Url;
+use serde::{Deserialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+use super::auth::random_token;
+use crate::{config, entity::error::AppError};
+
+/// The parts of an issuer's `/.well-known/openid-configuration` the login uses.
+#[derive(Debug, Deserialize)]
+pub struct Discovery {
+    pub authorization_endpoint: String,
+    pub issuer: String,
+    pub jwks_uri: String,
+    pub token_endpoint: String,
+    /// `client_secret_basic` is assumed when the issuer doesn't say.
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+/// The claims of a validated ID token that identify the user.
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub nonce: String,
+}
+
+/// What a login has to remember between sending the browser to the issuer and its
+/// callback: the `state` tying the callback to this browser, the `nonce` the ID token
+/// must echo and the PKCE (RFC 7636) code verifier.
+pub struct AuthorizationRequest {
+    pub code_verifier: String,
+    pub nonce: String,
+    pub state: String,
+}
+
+impl AuthorizationRequest {
+    pub fn new() -> Self {
+        AuthorizationRequest {
+            code_verifier: random_token(),
+            nonce: random_token(),
+            state: random_token(),
+        }
+    }
+
+    /// The `S256` challenge sent in place of the verifier.
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+
+    /// The three values joined by `.`, which base64url never contains.
+    pub fn to_cookie_value(&self) -> String {
+        format!("{}.{}.{}", self.state, self.nonce, self.code_verifier)
+    }
+
+    pub fn from_cookie_value(value: &str) -> Option<Self> {
+        let mut parts = value.split('.');
+        let request = AuthorizationRequest {
+            state: parts.next()?.to_string(),
+            nonce: parts.next()?.to_string(),
+            code_verifier: parts.next()?.to_string(),
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(request)
+    }
+}
+
+impl Default for AuthorizationRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Logs users in with an OpenID Connect issuer using the authorization code flow. The
+/// discovery document and keys are fetched on every login so rotations need no restart.
+pub struct Oidc {
+    client: reqwest::Client,
+    client_id: String,
+    client_secret: String,
+    issuer: String,
+    redirect_url: String,
+    scopes: String,
+}
+
+impl Oidc {
+    pub fn new(
+        issuer: &str,
+        client_id: &str,
+        client_secret: &str,
+        redirect_url: &str,
+    ) -> Arc<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("building the OIDC HTTP client");
+        Arc::new(Oidc {
+            client,
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            issuer: issuer.to_string(),
+            redirect_url: redirect_url.to_string(),
+            scopes: config::oidc_scopes(),
+        })
+    }
+
+    /// `None` unless `OIDC_ISSUER` is set.
+    pub fn from_config() -> Option<Arc<Self>> {
+        let issuer = config::oidc_issuer();
+        if issuer.is_empty() {
+            return None;
+        }
+
+        Some(Oidc::new(
+            &issuer,
+            &config::oidc_client_id(),
+            &config::oidc_client_secret(),
+            &config::oidc_redirect_url(),
+        ))
+    }
+
+    pub async fn discover(&self) -> Result<Discovery, AppError> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.issuer.trim_end_matches('/')
+        );
+        let discovery: Discovery = self.get_json(&url).await?;
+        if discovery.issuer != self.issuer {
+            return Err(oidc_error(format!(
+                "discovery document is for issuer '{}'",
+                discovery.issuer
+            )));
+        }
+
+        Ok(discovery)
+    }
+
+    /// Where to send the browser to log in.
+    pub fn authorization_url(
+        &self,
+        discovery: &Discovery,
+        request: &AuthorizationRequest,
+    ) -> Result<String, AppError> {
+        let params = [
+            ("client_id", self.client_id.as_str()),
+            ("code_challenge", &request.code_challenge()),
+            ("code_challenge_method", "S256"),
+            ("nonce", &request.nonce),
+            ("redirect_uri", &self.redirect_url),
+            ("response_type", "code"),
+            ("scope", &self.scopes),
+            ("state", &request.state),
+        ];
+        let url = Url::parse_with_params(&discovery.authorization_endpoint, params)
+            .map_err(oidc_error)?;
+        Ok(url.to_string())
+    }
+
+    /// Redeems the `code` the issuer redirected back with and returns the claims of the
+    /// ID token it was exchanged for, once validated.
+    pub async fn exchange_code(
+        &self,
+        discovery: &Discovery,
+        code: &str,
+        request: &AuthorizationRequest,
+    ) -> Result<IdTokenClaims, AppError> {
+        let mut form = vec![
+            ("code", code),
+            ("code_verifier", &request.code_verifier),
+            ("grant_type", "authorization_code"),
+            ("redirect_uri", &self.redirect_url),
+        ];
+        let methods = &discovery.token_endpoint_auth_methods_supported;
+        let mut req = self.client.post(&discovery.token_endpoint);
+        if self.client_secret.is_empty() {
+            form.push(("client_id", &self.client_id));
+        } else if methods.is_empty() || methods.iter().any(|m| m == "client_secret_basic") {
+            req = req.basic_auth(&self.client_id, Some(&self.client_secret));
+        } else {
+            form.push(("client_id", &self.client_id));
+            form.push(("client_secret", &self.client_secret));
+        }
+
+        let res = req.form(&form).send().await.map_err(oidc_error)?;
+        if res.status().is_client_error() {
+            let body = res.text().await.unwrap_or_default();
+            log::debug!("OIDC token endpoint rejected the code: {body}");
+            return Err(AppError::Unauthorized(
+                "Authorization code was rejected".to_string(),
+            ));
+        }
+        let body: TokenResponse = res
+            .error_for_status()
+            .map_err(oidc_error)?
+            .json()
+            .await
+            .map_err(oidc_error)?;
+
+        self.verify_id_token(discovery, &body.id_token, &request.nonce)
+            .await
+    }
+
+    async fn verify_id_token(
+        &self,
+        discovery: &Discovery,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid_id_token)?;
+        // Symmetric algorithms would have the token signed with our own client secret.
+        if !matches!(
+            header.alg,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+                | Algorithm::ES256
+                | Algorithm::ES384
+                | Algorithm::EdDSA
+        ) {
+            return Err(invalid_id_token(format!(
+                "unsupported algorithm {:?}",
+                header.alg
+            )));
+        }
+
+        let jwks: JwkSet = self.get_json(&discovery.jwks_uri).await?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        };
+        let Some(jwk) = jwk else {
+            return Err(invalid_id_token("no matching key"));
+        };
+        let key = DecodingKey::from_jwk(jwk).map_err(invalid_id_token)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_required_spec_claims(&["aud", "exp", "iss", "sub"]);
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid_id_token)?
+            .claims;
+        if claims.nonce != nonce {
+            return Err(invalid_id_token("nonce doesn't match"));
+        }
+
+        Ok(claims)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(oidc_error)?
+            .json()
+            .await
+            .map_err(oidc_error)
+    }
+}
+
+fn invalid_id_token(err: impl ToString) -> AppError {
+    log::debug!("Invalid ID token: {}", err.to_string());
+    AppError::Unauthorized("Invalid ID token".to_string())
+}
+
+fn oidc_error(err: impl ToString) -> AppError {
+    AppError::Internal {
+        err: format!("OIDC request failed: {}", err.to_string()),
+        path: format!("{}:{}:{}", file!(), line!(), column!()),
+    }
+}
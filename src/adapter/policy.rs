@@ -0,0 +1,104 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{
+    config,
+    entity::{
+        comment,
+        error::AppError,
+        post,
+        user::{self, Role},
+    },
+    repository::Repository,
+};
+
+/// What a user is attempting to do with a resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Delete,
+    Read,
+    Update,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Create => write!(f, "create"),
+            Action::Delete => write!(f, "delete"),
+            Action::Read => write!(f, "read"),
+            Action::Update => write!(f, "update"),
+        }
+    }
+}
+
+/// Implemented by resources that can be guarded by `authorize`.
+pub trait Policy {
+    /// Name used in the `Forbidden` message, e.g. "post".
+    const NAME: &'static str;
+
+    fn allows(&self, user: &user::Model, action: Action) -> bool;
+}
+
+/// Returns `AppError::Forbidden` unless `user` may perform `action` on `resource`.
+pub fn authorize<T: Policy>(
+    user: &user::Model,
+    action: Action,
+    resource: &T,
+) -> Result<(), AppError> {
+    if resource.allows(user, action) {
+        return Ok(());
+    }
+
+    Err(AppError::Forbidden(format!(
+        "You can't {action} this {}",
+        T::NAME
+    )))
+}
+
+/// Returns `AppError::Forbidden` unless the user has verified their email, for actions
+/// gated by `config::require_verified_email`. Access tokens don't carry the verification
+/// state, so it's read from the repository.
+pub async fn require_verified(repo: &dyn Repository, user_id: Uuid) -> Result<(), AppError> {
+    if !config::require_verified_email() {
+        return Ok(());
+    }
+
+    match repo.get_user_by_id(user_id).await?.email_verified_at {
+        Some(_) => Ok(()),
+        None => Err(AppError::Forbidden(
+            "You must verify your email first".to_string(),
+        )),
+    }
+}
+
+impl Policy for post::Model {
+    const NAME: &'static str = "post";
+
+    /// Anyone can read and create posts, authors can change their own and admins can
+    /// moderate any post.
+    fn allows(&self, user: &user::Model, action: Action) -> bool {
+        match action {
+            Action::Create | Action::Read => true,
+            Action::Delete | Action::Update => {
+                user.role == Role::Admin || self.user.as_ref().is_some_and(|u| u.id == user.id)
+            }
+        }
+    }
+}
+
+impl Policy for comment::Model {
+    const NAME: &'static str = "comment";
+
+    /// Anyone can read and comment, only authors can edit their comments and admins can
+    /// also delete any comment.
+    fn allows(&self, user: &user::Model, action: Action) -> bool {
+        let is_author = self.user.as_ref().is_some_and(|u| u.id == user.id);
+        match action {
+            Action::Create | Action::Read => true,
+            Action::Delete => user.role == Role::Admin || is_author,
+            Action::Update => is_author,
+        }
+    }
+}
@@ -1,14 +1,91 @@
 use std::env;
 
 pub const ACCESS_TOKEN_COOKIE_NAME: &str = "access_token";
-pub const ACCESS_TOKEN_TTL_IN_SECONDS: i64 = 60 * 60 * 24;
+pub const ACCESS_TOKEN_TTL_IN_SECONDS: i64 = 60 * 15;
+/// Starts every API key, which is how they are told apart from access tokens.
+pub const API_KEY_PREFIX: &str = "rra_";
+pub const API_KEY_MAX_EXPIRES_IN_SECONDS: i64 = 60 * 60 * 24 * 365;
+pub const COLLECTION_API_KEYS: &str = "api_keys";
+pub const COLLECTION_COMMENTS: &str = "comments";
+pub const COLLECTION_LOGIN_ATTEMPTS: &str = "login_attempts";
+pub const COLLECTION_MIGRATIONS: &str = "migrations";
+pub const COLLECTION_ONE_TIME_TOKENS: &str = "one_time_tokens";
 pub const COLLECTION_POSTS: &str = "posts";
+pub const COLLECTION_REACTIONS: &str = "reactions";
+pub const COLLECTION_REFRESH_TOKENS: &str = "refresh_tokens";
+pub const COLLECTION_REVOKED_TOKENS: &str = "revoked_tokens";
+pub const COLLECTION_SESSIONS: &str = "sessions";
 pub const COLLECTION_USERS: &str = "users";
+pub const EMAIL_VERIFICATION_TOKEN_TTL_IN_SECONDS: i64 = 60 * 60 * 24;
+/// Failed logins are forgotten this long after the last one.
+pub const LOGIN_ATTEMPT_TTL_IN_SECONDS: i64 = 60 * 60 * 24;
+/// The first lockout, doubled by every failure after it up to `LOGIN_LOCKOUT_MAX_IN_SECONDS`.
+pub const LOGIN_LOCKOUT_BASE_IN_SECONDS: i64 = 30;
+pub const LOGIN_LOCKOUT_MAX_IN_SECONDS: i64 = 60 * 60;
+pub const MFA_CHALLENGE_TTL_IN_SECONDS: i64 = 60 * 5;
+/// Wrong codes for a user's MFA challenges before the challenge is burnt and the user
+/// locked out of them like a failed login would.
+pub const MFA_MAX_FAILURES: i32 = 5;
+pub const MFA_RECOVERY_CODE_COUNT: usize = 10;
+/// Recovery codes are replaced whenever MFA is set up again, this only bounds how long
+/// an unused one stays valid.
+pub const MFA_RECOVERY_CODE_TTL_IN_SECONDS: i64 = 60 * 60 * 24 * 365 * 10;
+/// Holds an OIDC login's state, nonce and PKCE verifier until the issuer redirects back.
+pub const OIDC_COOKIE_NAME: &str = "oidc";
+pub const OIDC_COOKIE_PATH: &str = "/auth/oidc";
+pub const OIDC_LOGIN_TTL_IN_SECONDS: i64 = 60 * 10;
+pub const PASSWORD_RESET_TOKEN_TTL_IN_SECONDS: i64 = 60 * 60;
+/// How recently an account without a password must have signed in to confirm changes
+/// that otherwise need the password.
+pub const REAUTHENTICATION_WINDOW_IN_SECONDS: i64 = 60 * 5;
+pub const REFRESH_TOKEN_COOKIE_NAME: &str = "refresh_token";
+pub const REFRESH_TOKEN_COOKIE_PATH: &str = "/auth";
+pub const REFRESH_TOKEN_TTL_IN_SECONDS: i64 = 60 * 60 * 24 * 30;
+/// How stale a session's `last_seen_at` may get before a request updates it, so most
+/// requests don't write to the database.
+pub const SESSION_LAST_SEEN_INTERVAL_IN_SECONDS: i64 = 60;
+
+/// Argon2 memory cost in KiB for new password hashes, existing ones are upgraded as
+/// their users log in.
+pub fn argon2_memory_cost() -> u32 {
+    env::var("ARGON2_MEMORY_COST")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(argon2::Params::DEFAULT_M_COST)
+}
+
+pub fn argon2_parallelism() -> u32 {
+    env::var("ARGON2_PARALLELISM")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(argon2::Params::DEFAULT_P_COST)
+}
+
+/// Argon2 iterations for new password hashes.
+pub fn argon2_time_cost() -> u32 {
+    env::var("ARGON2_TIME_COST")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(argon2::Params::DEFAULT_T_COST)
+}
 
 pub fn access_token_secret() -> String {
     return env::var("ACCESS_TOKEN_SECRET").unwrap_or(String::new());
 }
 
+/// Comma-separated `kid:algorithm[:path]` keys access tokens are verified with, the first
+/// one also signs them. `path` is a PEM private key for `RS256` and `EdDSA`, or a file
+/// holding the secret for `HS256`, which falls back to `ACCESS_TOKEN_SECRET` without one.
+pub fn access_token_keys() -> String {
+    env::var("ACCESS_TOKEN_KEYS").unwrap_or_default()
+}
+
+/// Comma-separated places to look for the access token, in order of precedence. The
+/// first source that carries a token wins. Supports `cookie` and `header`.
+pub fn auth_token_sources() -> String {
+    env::var("AUTH_TOKEN_SOURCES").unwrap_or(String::from("cookie,header"))
+}
+
 pub fn address() -> String {
     let port = env::var("PORT").unwrap_or(String::from("5000"));
     format!("0.0.0.0:{}", port)
@@ -30,6 +107,80 @@ pub fn framework_type() -> String {
     return env::var("FRAMEWORK_TYPE").unwrap_or(String::new());
 }
 
+/// Failed logins to one account before it is locked out.
+pub fn login_max_failures_per_account() -> i32 {
+    env::var("LOGIN_MAX_FAILURES_PER_ACCOUNT")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(5)
+}
+
+/// Failed logins from one IP address, across accounts, before it is locked out.
+pub fn login_max_failures_per_ip() -> i32 {
+    env::var("LOGIN_MAX_FAILURES_PER_IP")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(20)
+}
+
+/// Where `MAILER_TYPE=file` writes mail to.
+pub fn mailer_dir() -> String {
+    env::var("MAILER_DIR").unwrap_or(String::from("mail"))
+}
+
+/// Either `log` (the default) or `file`.
+pub fn mailer_type() -> String {
+    env::var("MAILER_TYPE").unwrap_or(String::from("log"))
+}
+
+/// Shown next to the account in authenticator apps.
+pub fn mfa_issuer() -> String {
+    env::var("MFA_ISSUER").unwrap_or(String::from("rust-rest-api"))
+}
+
+pub fn oidc_client_id() -> String {
+    env::var("OIDC_CLIENT_ID").unwrap_or_default()
+}
+
+/// Empty for public clients, which rely on PKCE alone.
+pub fn oidc_client_secret() -> String {
+    env::var("OIDC_CLIENT_SECRET").unwrap_or_default()
+}
+
+/// The OpenID Connect issuer users can log in with, e.g. `https://accounts.google.com`.
+/// OIDC login is disabled unless it is set.
+pub fn oidc_issuer() -> String {
+    env::var("OIDC_ISSUER").unwrap_or_default()
+}
+
+/// Where the issuer sends users back to, this API's `/auth/oidc/callback` as registered
+/// with the issuer.
+pub fn oidc_redirect_url() -> String {
+    env::var("OIDC_REDIRECT_URL").unwrap_or_default()
+}
+
+/// Space-separated scopes to request, `openid` and `email` are needed to log in.
+pub fn oidc_scopes() -> String {
+    env::var("OIDC_SCOPES").unwrap_or(String::from("openid email profile"))
+}
+
+/// What happens to a deleted user's posts and comments, `delete` or `reassign` to a
+/// tombstone author.
+pub fn orphaned_posts() -> String {
+    env::var("ORPHANED_POSTS").unwrap_or(String::from("delete"))
+}
+
+/// Blocks post and comment creation until the author has verified their email.
+pub fn require_verified_email() -> bool {
+    env::var("REQUIRE_VERIFIED_EMAIL").unwrap_or(String::from("false")) == "true"
+}
+
+/// Takes the client IP from the last `X-Forwarded-For` entry, only enable it behind a
+/// proxy that sets the header.
+pub fn trust_forwarded_for() -> bool {
+    env::var("TRUST_FORWARDED_FOR").unwrap_or(String::from("false")) == "true"
+}
+
 pub fn is_production() -> bool {
     return env::var("RUST_ENV").unwrap_or(String::from("development"))
         == String::from("production");
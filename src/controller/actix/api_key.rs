@@ -0,0 +1,86 @@
+use actix_web::{HttpResponse, delete, get, post, web};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    adapter::AuthUser,
+    config,
+    entity::{api_key, error::AppError},
+};
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 64, message = "Name must be 1 to 64 characters"))]
+    pub name: String,
+    #[serde(default)]
+    pub scope: api_key::Scope,
+    /// Seconds until the key expires, it never does without one.
+    #[validate(range(
+        min = 60,
+        max = config::API_KEY_MAX_EXPIRES_IN_SECONDS,
+        message = "Expiry must be between 60 seconds and a year"
+    ))]
+    pub expires_in: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: api_key::Model,
+    /// The only time the key is shown.
+    pub key: String,
+}
+
+#[post("/auth/api-keys")]
+pub async fn create_api_key(
+    body: web::Json<CreateApiKeyRequest>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let body = body.into_inner();
+    let expires_at = api_key::expires_at(body.expires_in).map_err(AppError::Validation)?;
+    let (key, api_key) = auth
+        .generate_api_key(&user, body.name, body.scope, expires_at)
+        .await?;
+    let res = HttpResponse::Created().json(CreateApiKeyResponse {
+        api_key: api_key.set_key_hash("".to_string()),
+        key,
+    });
+    Ok(res)
+}
+
+#[delete("/auth/api-keys/{id}")]
+pub async fn delete_api_key(
+    id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    repo.delete_api_key(id.into_inner(), user.id).await?;
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[get("/auth/api-keys")]
+pub async fn get_api_keys(
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let api_keys: Vec<_> = repo
+        .get_api_keys(user.id)
+        .await?
+        .into_iter()
+        .map(|api_key| api_key.set_key_hash("".to_string()))
+        .collect();
+    let res = HttpResponse::Ok().json(api_keys);
+    Ok(res)
+}
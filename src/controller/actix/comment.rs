@@ -0,0 +1,125 @@
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
+    entity::{comment, error::AppError},
+};
+
+#[derive(Debug, Deserialize, Validate)]
+struct CreateCommentRequest {
+    #[validate(length(min = 1, message = "Body must not be empty"))]
+    body: String,
+    /// The comment on the same post this one replies to.
+    #[serde(default)]
+    parent_id: Option<Uuid>,
+}
+
+#[post("/posts/{id}/comments")]
+pub async fn create_comment(
+    body: web::Json<CreateCommentRequest>,
+    post_id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let post_id = post_id.into_inner();
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+    if let Some(parent_id) = body.parent_id {
+        let parent = repo.get_comment_by_id(parent_id).await?;
+        if parent.post_id != post.id {
+            return Err(AppError::NotFound(format!(
+                "Comment '{parent_id}' not found on post '{post_id}'"
+            )));
+        }
+    }
+
+    let mut comment = comment::Model {
+        body: body.body.clone(),
+        parent_id: body.parent_id,
+        post_id: post.id,
+        user_id: user.id,
+        ..Default::default()
+    };
+    policy::require_verified(repo.as_ref(), user.id).await?;
+    policy::authorize(&user, Action::Create, &comment)?;
+    comment = repo.create_comment(comment).await?;
+
+    let res = HttpResponse::Created().json(comment.set_user(user));
+    Ok(res)
+}
+
+#[delete("/comments/{id}")]
+pub async fn delete_comment(
+    id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+
+    let comment = repo.get_comment_by_id(id.into_inner()).await?;
+    policy::authorize(&user, Action::Delete, &comment)?;
+
+    repo.delete_comment_by_id(comment.id).await?;
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+/// The post's comments and replies, oldest first, to be threaded by `parent_id`.
+#[get("/posts/{id}/comments")]
+pub async fn get_comments(
+    post_id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let post = repo.get_post_by_id(post_id.into_inner()).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    let comments = repo.get_comments(post.id).await?;
+    let res = HttpResponse::Ok().json(comments);
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct UpdateCommentRequest {
+    #[validate(length(min = 1, message = "Body must not be empty"))]
+    body: String,
+}
+
+#[put("/comments/{id}")]
+pub async fn update_comment(
+    body: web::Json<UpdateCommentRequest>,
+    id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let mut comment = repo.get_comment_by_id(id.into_inner()).await?;
+    policy::authorize(&user, Action::Update, &comment)?;
+    comment = comment::Model {
+        body: body.body.clone(),
+        updated_at: chrono::Utc::now(),
+        ..comment
+    };
+
+    comment = repo.update_comment(comment).await?;
+    let res = HttpResponse::Ok().json(comment);
+    Ok(res)
+}
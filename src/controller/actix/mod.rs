@@ -1,9 +1,19 @@
+mod api_key;
+mod comment;
+mod oidc;
 mod others;
 mod post;
+mod reaction;
+mod session;
 mod user;
 
+pub use api_key::*;
+pub use comment::*;
+pub use oidc::*;
 pub use others::*;
 pub use post::*;
+pub use reaction::*;
+pub use session::*;
 pub use user::*;
 
 use actix_web::{
@@ -17,9 +27,12 @@ use crate::{AppState, adapter, config, repository};
 
 #[tokio::main]
 pub async fn main() -> std::io::Result<()> {
+    let repo = repository::new().await.unwrap();
     let state = web::Data::new(AppState {
-        repo: repository::new().await.unwrap(),
-        auth: adapter::Auth::new(),
+        auth: adapter::Auth::new(repo.clone()),
+        mailer: adapter::new_mailer(),
+        oidc: adapter::Oidc::from_config(),
+        repo,
     });
 
     HttpServer::new(move || new_app(state.clone()).wrap(Logger::default()))
@@ -42,12 +55,41 @@ pub fn new_app(
     App::new()
         .app_data(state)
         .service(check_health)
+        .service(get_jwks)
+        .service(create_api_key)
+        .service(delete_api_key)
+        .service(get_api_keys)
         .service(create_post)
         .service(delete_post)
+        // Before `get_post`, whose `/posts/{id}` would otherwise match it.
+        .service(search_posts)
         .service(get_post)
         .service(get_posts)
         .service(update_post)
+        .service(create_comment)
+        .service(delete_comment)
+        .service(get_comments)
+        .service(update_comment)
+        .service(create_reaction)
+        .service(delete_reaction)
         .service(get_auth_user)
+        .service(update_auth_user)
+        .service(delete_auth_user)
+        .service(change_password)
+        .service(disable_mfa)
+        .service(confirm_mfa)
+        .service(setup_mfa)
+        .service(verify_mfa)
         .service(login_user)
+        .service(oidc_callback)
+        .service(login_with_oidc)
+        .service(logout_user)
+        .service(forgot_password)
+        .service(reset_password)
+        .service(refresh_access_token)
         .service(register_user)
+        .service(delete_session)
+        .service(get_sessions)
+        .service(verify_email)
+        .service(resend_email_verification)
 }
@@ -0,0 +1,109 @@
+use actix_web::{
+    HttpRequest, HttpResponse,
+    cookie::{Cookie, SameSite, time::Duration},
+    get,
+    http::header,
+    web,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{TokenQuery, mfa_challenge, token_response};
+use crate::{
+    AppState,
+    adapter::{AuthorizationRequest, ClientIp, Oidc, UserAgent},
+    config,
+    entity::error::AppError,
+};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct OidcCallbackQuery {
+    #[serde(default)]
+    pub code: String,
+    /// Set instead of `code` when the issuer didn't log the user in.
+    #[serde(default)]
+    pub error: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+/// Starts an OIDC login by sending the browser to the issuer.
+#[get("/auth/oidc/login")]
+pub async fn login_with_oidc(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let oidc = find_oidc(state.get_ref())?;
+    let discovery = oidc.discover().await?;
+    let request = AuthorizationRequest::new();
+    let url = oidc.authorization_url(&discovery, &request)?;
+
+    let res = HttpResponse::SeeOther()
+        .cookie(oidc_cookie(request.to_cookie_value()))
+        .insert_header((header::LOCATION, url))
+        .finish();
+    Ok(res)
+}
+
+/// Where the issuer sends the browser back to. Logs in the account with the ID token's
+/// verified email, creating it on first use, like `login_user` would.
+#[get("/auth/oidc/callback")]
+pub async fn oidc_callback(
+    req: HttpRequest,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    query: web::Query<OidcCallbackQuery>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    let oidc = find_oidc(state.get_ref())?;
+    let request = req
+        .cookie(config::OIDC_COOKIE_NAME)
+        .and_then(|cookie| AuthorizationRequest::from_cookie_value(cookie.value()))
+        .filter(|request| request.state == query.state);
+    let Some(request) = request else {
+        return Err(AppError::Unauthorized(
+            "Login state is missing or doesn't match".to_string(),
+        ));
+    };
+    if !query.error.is_empty() {
+        return Err(AppError::Unauthorized(format!(
+            "Identity provider returned '{}'",
+            query.error
+        )));
+    }
+
+    let discovery = oidc.discover().await?;
+    let claims = oidc
+        .exchange_code(&discovery, &query.code, &request)
+        .await?;
+    let user = auth.find_or_create_oidc_user(&claims).await?;
+    let mut builder = HttpResponse::Ok();
+    let mut cookie = oidc_cookie("".to_string());
+    cookie.make_removal();
+    builder.cookie(cookie);
+    if user.mfa_enabled_at.is_some() {
+        let body = mfa_challenge(auth, &user).await?;
+        return Ok(builder.json(body));
+    }
+
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(builder, TokenQuery::default(), user, tokens);
+    Ok(res)
+}
+
+fn find_oidc(state: &AppState) -> Result<&Oidc, AppError> {
+    match &state.oidc {
+        Some(oidc) => Ok(oidc),
+        None => Err(AppError::NotFound(
+            "OIDC login is not configured".to_string(),
+        )),
+    }
+}
+
+/// `Lax` so it is sent along when the issuer redirects back.
+fn oidc_cookie(value: String) -> Cookie<'static> {
+    Cookie::build(config::OIDC_COOKIE_NAME, value)
+        .http_only(true)
+        .max_age(Duration::seconds(config::OIDC_LOGIN_TTL_IN_SECONDS))
+        .path(config::OIDC_COOKIE_PATH)
+        .same_site(SameSite::Lax)
+        .secure(config::is_production())
+        .finish()
+}
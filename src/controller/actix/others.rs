@@ -1,4 +1,4 @@
-use actix_web::{HttpResponse, get, web};
+use actix_web::{HttpResponse, get, http::header, web};
 use serde_json::json;
 
 use crate::{AppState, entity::error::AppError};
@@ -9,3 +9,12 @@ pub async fn check_health(state: web::Data<AppState>) -> Result<HttpResponse, Ap
     let res = HttpResponse::Ok().json(json!({"status": "ok"}));
     Ok(res)
 }
+
+/// Public keys for verifying access tokens, cached briefly so rotations propagate.
+#[get("/.well-known/jwks.json")]
+pub async fn get_jwks(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let res = HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+        .json(state.auth.jwks());
+    Ok(res)
+}
@@ -1,4 +1,4 @@
-use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
+use actix_web::{HttpResponse, delete, get, http::header, post, put, web};
 use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
@@ -6,6 +6,10 @@ use validator::Validate;
 
 use crate::{
     AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
     entity::{error::AppError, post},
 };
 
@@ -20,11 +24,10 @@ struct CreatePostRequest {
 #[post("/posts")]
 pub async fn create_post(
     body: web::Json<CreatePostRequest>,
-    req: HttpRequest,
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    let user = auth.extract_user_from_actix(req)?;
+    let AppState { repo, .. } = state.get_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
@@ -35,6 +38,8 @@ pub async fn create_post(
         user_id: user.id,
         ..Default::default()
     };
+    policy::require_verified(repo.as_ref(), user.id).await?;
+    policy::authorize(&user, Action::Create, &post)?;
     post = repo.create_post(post).await?;
 
     let res = HttpResponse::Created().json(post.set_user(user));
@@ -44,16 +49,13 @@ pub async fn create_post(
 #[delete("/posts/{id}")]
 pub async fn delete_post(
     id: web::Path<Uuid>,
-    req: HttpRequest,
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    let user = auth.extract_user_from_actix(req)?;
+    let AppState { repo, .. } = state.get_ref();
 
     let post = repo.get_post_by_id(id.into_inner()).await?;
-    if post.user.unwrap().id != user.id {
-        return Err(AppError::Forbidden("You can't delete this post".to_string()));
-    }
+    policy::authorize(&user, Action::Delete, &post)?;
 
     repo.delete_post_by_id(post.id).await?;
     let res = HttpResponse::Ok().json(json!({"message": "Success"}));
@@ -63,29 +65,67 @@ pub async fn delete_post(
 #[get("/posts")]
 pub async fn get_posts(
     query: web::Query<post::Pagination>,
-    req: HttpRequest,
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    auth.extract_user_from_actix(req)?;
+    let AppState { repo, .. } = state.get_ref();
     if let Err(err) = query.validate() {
         return Err(AppError::Validation(err));
     }
 
-    let posts = repo.get_posts(query.into_inner()).await?;
-    let res = HttpResponse::Ok().json(json!(posts));
+    let (posts, total) = repo.get_posts(query.with_lookahead()).await?;
+    let mut page = post::Page::new(posts, total, &query);
+    let ids = page.items.iter().map(|post| post.id).collect();
+    let mut summaries = repo.get_reaction_summaries(ids, user.id).await?;
+    for post in page.items.iter_mut() {
+        post.reactions = Some(summaries.remove(&post.id).unwrap_or_default());
+    }
+    let mut builder = HttpResponse::Ok();
+    if let Some(links) = page.links("/posts", &query) {
+        builder.insert_header((header::LINK, links));
+    }
+    let res = builder.json(page);
+    Ok(res)
+}
+
+/// Posts matching every word of `q` in their title or content, best matches first.
+#[get("/posts/search")]
+pub async fn search_posts(
+    query: web::Query<post::SearchQuery>,
+    _: AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    if let Err(err) = query.validate() {
+        return Err(AppError::Validation(err));
+    }
+    if let Err(err) = query.pagination().validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let query = query.into_inner();
+    let (items, total) = repo.search_posts(query.clone()).await?;
+    let results = post::SearchResults {
+        items,
+        limit: query.limit,
+        offset: query.offset,
+        total,
+    };
+    let res = HttpResponse::Ok().json(results);
     Ok(res)
 }
 
 #[get("/posts/{id}")]
 pub async fn get_post(
     id: web::Path<Uuid>,
-    req: HttpRequest,
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    auth.extract_user_from_actix(req)?;
-    let post = repo.get_post_by_id(id.into_inner()).await?;
+    let AppState { repo, .. } = state.get_ref();
+    let mut post = repo.get_post_by_id(id.into_inner()).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    post.reactions = Some(summaries.remove(&post.id).unwrap_or_default());
 
     let res = HttpResponse::Ok().json(post);
     Ok(res)
@@ -103,16 +143,16 @@ struct UpdatePostRequest {
 pub async fn update_post(
     body: web::Json<UpdatePostRequest>,
     id: web::Path<Uuid>,
-    req: HttpRequest,
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    let user = auth.extract_user_from_actix(req)?;
+    let AppState { repo, .. } = state.get_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
 
     let mut post = repo.get_post_by_id(id.into_inner()).await?;
+    policy::authorize(&user, Action::Update, &post)?;
     post = post::Model {
         content: body.content.clone(),
         title: body.title.clone(),
@@ -120,10 +160,6 @@ pub async fn update_post(
         ..post
     };
 
-    if post.user.clone().unwrap().id != user.id {
-        return Err(AppError::Forbidden("You can't update this post".to_string()));
-    }
-
     post = repo.update_post(post).await?;
     let res = HttpResponse::Ok().json(post);
     Ok(res)
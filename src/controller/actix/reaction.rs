@@ -0,0 +1,58 @@
+use actix_web::{HttpResponse, delete, put, web};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
+    entity::{error::AppError, reaction},
+};
+
+/// Reacts to the post, returning its reactions. Reacting twice with the same kind is a
+/// no-op.
+#[put("/posts/{id}/reactions/{kind}")]
+pub async fn create_reaction(
+    path: web::Path<(Uuid, String)>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let (post_id, kind) = path.into_inner();
+    let kind: reaction::Kind = kind.parse().map_err(AppError::NotFound)?;
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    let reaction = reaction::Model {
+        kind,
+        post_id: post.id,
+        user_id: user.id,
+        ..Default::default()
+    };
+    repo.create_reaction(reaction).await?;
+
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    let res = HttpResponse::Ok().json(summaries.remove(&post.id).unwrap_or_default());
+    Ok(res)
+}
+
+/// Takes back the reaction, returning the post's reactions. Succeeds if there was none.
+#[delete("/posts/{id}/reactions/{kind}")]
+pub async fn delete_reaction(
+    path: web::Path<(Uuid, String)>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let (post_id, kind) = path.into_inner();
+    let kind: reaction::Kind = kind.parse().map_err(AppError::NotFound)?;
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    repo.delete_reaction(post.id, user.id, kind).await?;
+
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    let res = HttpResponse::Ok().json(summaries.remove(&post.id).unwrap_or_default());
+    Ok(res)
+}
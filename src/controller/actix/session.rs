@@ -0,0 +1,65 @@
+use actix_web::{HttpResponse, delete, get, web};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    adapter::AuthUser,
+    entity::{error::AppError, session},
+};
+
+/// A session as its user sees it, `expires_at` as a plain timestamp rather than the
+/// BSON date it is stored as.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub last_seen_at: DateTime<Utc>,
+    pub user_agent: String,
+}
+
+impl From<session::Model> for SessionResponse {
+    fn from(session: session::Model) -> Self {
+        SessionResponse {
+            id: session.id,
+            created_at: session.created_at,
+            expires_at: session.expires_at,
+            ip: session.ip,
+            last_seen_at: session.last_seen_at,
+            user_agent: session.user_agent,
+        }
+    }
+}
+
+/// Signs the session out, whichever device it is on.
+#[delete("/auth/sessions/{id}")]
+pub async fn delete_session(
+    id: web::Path<Uuid>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    auth.revoke_session(id.into_inner(), user.id).await?;
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[get("/auth/sessions")]
+pub async fn get_sessions(
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let sessions: Vec<SessionResponse> = repo
+        .get_sessions(user.id)
+        .await?
+        .into_iter()
+        .map(SessionResponse::from)
+        .collect();
+    let res = HttpResponse::Ok().json(sessions);
+    Ok(res)
+}
@@ -1,16 +1,42 @@
 use actix_web::{
-    HttpRequest, HttpResponse,
+    HttpRequest, HttpResponse, HttpResponseBuilder,
     cookie::{Cookie, SameSite, time::Duration},
-    get, post, web,
+    delete, get, patch, post, put, web,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use validator::Validate;
 
 use crate::{
-    AppState, config,
-    entity::{error::AppError, user},
+    AppState,
+    adapter::{Auth, AuthUser, ClientIp, Mail, Totp, UserAgent},
+    config,
+    entity::{error::AppError, one_time_token, user},
 };
 
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TokenQuery {
+    /// Returns the tokens in the response body for clients that can't use cookies.
+    #[serde(default)]
+    pub include_token: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub user: user::Model,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MfaChallengeResponse {
+    pub expires_in: i64,
+    pub mfa_required: bool,
+    pub mfa_token: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Validate)]
 pub struct LoginUserRequest {
     #[validate(email(message = "Email must be valid"))]
@@ -23,28 +49,24 @@ pub struct LoginUserRequest {
 
 pub async fn login_user(
     state: web::Data<AppState>,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    query: web::Query<TokenQuery>,
     body: web::Json<LoginUserRequest>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
+    let AppState { auth, .. } = state.get_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
 
-    let user = repo.get_user_by_email(body.email.clone()).await?;
-    auth.verify_password(&body.password, &user.password)?;
-    let token = auth.generate_access_token(&user)?;
+    let user = auth.authenticate(&body.email, &body.password, ip).await?;
+    if user.mfa_enabled_at.is_some() {
+        let body = mfa_challenge(auth, &user).await?;
+        return Ok(HttpResponse::Ok().json(body));
+    }
 
-    let res = HttpResponse::Ok()
-        .cookie(
-            Cookie::build(config::ACCESS_TOKEN_COOKIE_NAME, token)
-                .http_only(true)
-                .max_age(Duration::seconds(config::ACCESS_TOKEN_TTL_IN_SECONDS))
-                .path("/")
-                .same_site(SameSite::Lax)
-                .secure(config::is_production())
-                .finish(),
-        )
-        .json(user.set_password("".to_string()));
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(HttpResponse::Ok(), query.into_inner(), user, tokens);
     Ok(res)
 }
 
@@ -62,9 +84,12 @@ pub struct RegisterUserRequest {
 
 pub async fn register_user(
     state: web::Data<AppState>,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    query: web::Query<TokenQuery>,
     body: web::Json<RegisterUserRequest>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
+    let AppState { repo, auth, .. } = state.get_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
@@ -77,31 +102,546 @@ pub async fn register_user(
         ..Default::default()
     };
     user = repo.create_user(user).await?;
-    let token = auth.generate_access_token(&user)?;
+    // The account is usable without it, the user can ask for another mail.
+    if let Err(err) = send_email_verification(state.get_ref(), &user).await {
+        log::error!(
+            "Failed to send verification mail to '{}': {err}",
+            user.email
+        );
+    }
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(HttpResponse::Created(), query.into_inner(), user, tokens);
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[post("/auth/logout")]
+pub async fn logout_user(
+    body: Option<web::Json<RefreshTokenRequest>>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    // Expired or forged tokens are already rejected, so there is nothing to revoke.
+    if let Ok(token) = auth.extract_token_from_actix(&req)
+        && let Err(err) = auth.revoke_access_token(&token).await
+        && !matches!(err, AppError::Unauthorized(_))
+    {
+        return Err(err);
+    }
+
+    if let Some(token) = find_refresh_token(&req, body) {
+        auth.revoke_refresh_token(&token).await?;
+    }
+
+    let mut access_token = access_token_cookie(String::new());
+    access_token.make_removal();
+    let mut refresh_token = refresh_token_cookie(String::new());
+    refresh_token.make_removal();
+    let res = HttpResponse::Ok()
+        .cookie(access_token)
+        .cookie(refresh_token)
+        .json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[post("/auth/refresh")]
+pub async fn refresh_access_token(
+    body: Option<web::Json<RefreshTokenRequest>>,
+    query: web::Query<TokenQuery>,
+    req: HttpRequest,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    let Some(token) = find_refresh_token(&req, body) else {
+        let msg = format!(
+            "Cookie '{}' or request body refresh_token not found",
+            config::REFRESH_TOKEN_COOKIE_NAME
+        );
+        return Err(AppError::Unauthorized(msg));
+    };
 
-    let res = HttpResponse::Created()
-        .cookie(
-            Cookie::build(config::ACCESS_TOKEN_COOKIE_NAME, token)
-                .http_only(true)
-                .max_age(Duration::seconds(config::ACCESS_TOKEN_TTL_IN_SECONDS))
-                .path("/")
-                .same_site(SameSite::Lax)
-                .secure(config::is_production())
-                .finish(),
-        )
-        .json(user.set_password("".to_string()));
+    let (user, tokens) = auth.rotate_refresh_token(&token, ip, user_agent).await?;
+    let res = token_response(HttpResponse::Ok(), query.into_inner(), user, tokens);
     Ok(res)
 }
 
 #[get("/auth/me")]
 pub async fn get_auth_user(
+    AuthUser(user): AuthUser,
     state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let user = repo.get_user_by_id(user.id).await?;
+
+    let res = HttpResponse::Ok().json(user.set_password("".to_string()));
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct UpdateUserRequest {
+    /// Needed to change the email, unless the account has no password.
+    #[validate(length(min = 1, message = "Current password must not be empty"))]
+    pub current_password: Option<String>,
+    #[validate(email(message = "Email must be valid"))]
+    pub email: Option<String>,
+    #[validate(length(min = 1, message = "Name must not be empty"))]
+    pub name: Option<String>,
+}
+
+/// Changing the email needs the current password, as password resets are mailed to it.
+/// The new email is marked unverified and mailed a verification token.
+#[patch("/auth/me")]
+pub async fn update_auth_user(
+    body: web::Json<UpdateUserRequest>,
+    AuthUser(user): AuthUser,
+    ClientIp(ip): ClientIp,
     req: HttpRequest,
+    state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    let AppState { repo, auth } = state.get_ref();
-    let mut user = auth.extract_user_from_actix(req)?;
-    user = repo.get_user_by_id(user.id).await?;
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let mut user = repo.get_user_by_id(user.id).await?;
+    if let Some(name) = &body.name {
+        user.name = name.clone();
+    }
+    let email_changed = match &body.email {
+        Some(email) if *email != user.email => {
+            let token = auth.extract_token_from_actix(&req)?;
+            let current_password = body.current_password.as_deref();
+            auth.confirm_identity(&user, current_password, &token, ip)
+                .await?;
+            user.email = email.clone();
+            user.email_verified_at = None;
+            true
+        }
+        _ => false,
+    };
+    let user = repo.update_user(user).await?;
+    if email_changed && let Err(err) = send_email_verification(state.get_ref(), &user).await {
+        log::error!(
+            "Failed to send verification mail to '{}': {err}",
+            user.email
+        );
+    }
 
     let res = HttpResponse::Ok().json(user.set_password("".to_string()));
     Ok(res)
 }
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password must not be empty"))]
+    pub current_password: String,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
+    pub password: String,
+}
+
+/// Signs out every session, then starts a new one for the caller.
+#[put("/auth/me/password")]
+pub async fn change_password(
+    body: web::Json<ChangePasswordRequest>,
+    AuthUser(user): AuthUser,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    state: web::Data<AppState>,
+    query: web::Query<TokenQuery>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let user = repo.get_user_by_id(user.id).await?;
+    // Counted like a failed login, so a stolen token can't be used to guess it.
+    let user = auth
+        .authenticate(&user.email, &body.current_password, ip)
+        .await?;
+    let password = auth.hash_password(&body.password)?;
+    repo.update_user_password(user.id, password).await?;
+    repo.delete_one_time_tokens(user.id, one_time_token::Purpose::PasswordReset)
+        .await?;
+    auth.revoke_user_sessions(user.id).await?;
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(HttpResponse::Ok(), query.into_inner(), user, tokens);
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct DeleteUserRequest {
+    /// Left out by accounts without a password, which need a recent login instead.
+    #[validate(length(min = 1, message = "Password must not be empty"))]
+    pub password: Option<String>,
+}
+
+/// Deletes the account once the password or a recent login confirms it, deleting or
+/// reassigning its posts depending on `ORPHANED_POSTS`.
+#[delete("/auth/me")]
+pub async fn delete_auth_user(
+    body: web::Json<DeleteUserRequest>,
+    AuthUser(user): AuthUser,
+    ClientIp(ip): ClientIp,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    // A typo must not fall back to deleting the posts.
+    let posts = match config::orphaned_posts().parse() {
+        Ok(posts) => posts,
+        Err(err) => {
+            return Err(AppError::Internal {
+                err,
+                path: format!("{}:{}:{}", file!(), line!(), column!()),
+            });
+        }
+    };
+    let token = auth.extract_token_from_actix(&req)?;
+    let user = repo.get_user_by_id(user.id).await?;
+    auth.confirm_identity(&user, body.password.as_deref(), &token, ip)
+        .await?;
+    repo.delete_user_by_id(user.id, posts).await?;
+    auth.revoke_access_token(&token).await?;
+
+    let mut access_token = access_token_cookie(String::new());
+    access_token.make_removal();
+    let mut refresh_token = refresh_token_cookie(String::new());
+    refresh_token.make_removal();
+    let res = HttpResponse::Ok()
+        .cookie(access_token)
+        .cookie(refresh_token)
+        .json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 1, message = "Code must not be empty"))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MfaSetupResponse {
+    pub otpauth_uri: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct VerifyMfaRequest {
+    #[validate(length(min = 1, message = "Code must not be empty"))]
+    pub code: String,
+    #[validate(length(min = 1, message = "MFA token must not be empty"))]
+    pub mfa_token: String,
+}
+
+/// Starts enrolment with a new secret, MFA stays off until a code from it is confirmed.
+#[post("/auth/mfa/setup")]
+pub async fn setup_mfa(
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let user = repo.get_user_by_id(user.id).await?;
+    if user.mfa_enabled_at.is_some() {
+        return Err(AppError::Conflict("MFA is already enabled".to_string()));
+    }
+
+    let secret = Totp::generate_secret();
+    let otpauth_uri = Totp::new(&secret, &user.email)?.uri()?;
+    repo.update_user_mfa(user.id, Some(secret.clone()), None)
+        .await?;
+
+    let res = HttpResponse::Ok().json(MfaSetupResponse {
+        otpauth_uri,
+        secret,
+    });
+    Ok(res)
+}
+
+/// Enables MFA once the authenticator produced a valid code, returning the recovery
+/// codes. They are only shown this once.
+#[post("/auth/mfa/confirm")]
+pub async fn confirm_mfa(
+    body: web::Json<MfaCodeRequest>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let user = repo.get_user_by_id(user.id).await?;
+    if user.mfa_enabled_at.is_some() {
+        return Err(AppError::Conflict("MFA is already enabled".to_string()));
+    }
+    if user.mfa_secret.is_none() {
+        return Err(AppError::Conflict(
+            "MFA setup has not been started".to_string(),
+        ));
+    }
+
+    auth.verify_mfa_code(&user, &body.code).await?;
+    repo.update_user_mfa(user.id, user.mfa_secret.clone(), Some(chrono::Utc::now()))
+        .await?;
+    let recovery_codes = auth.generate_recovery_codes(&user).await?;
+
+    let res = HttpResponse::Ok().json(RecoveryCodesResponse { recovery_codes });
+    Ok(res)
+}
+
+#[delete("/auth/mfa")]
+pub async fn disable_mfa(
+    body: web::Json<MfaCodeRequest>,
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let user = repo.get_user_by_id(user.id).await?;
+    if user.mfa_enabled_at.is_none() {
+        return Err(AppError::Conflict("MFA is not enabled".to_string()));
+    }
+
+    auth.verify_mfa_code(&user, &body.code).await?;
+    repo.update_user_mfa(user.id, None, None).await?;
+    repo.delete_one_time_tokens(user.id, one_time_token::Purpose::MfaRecovery)
+        .await?;
+
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+/// The second step of `login_user` for users with MFA, exchanging the challenge token and
+/// a TOTP or recovery code for access and refresh tokens.
+#[post("/auth/mfa/verify")]
+pub async fn verify_mfa(
+    body: web::Json<VerifyMfaRequest>,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    query: web::Query<TokenQuery>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let user = auth
+        .verify_mfa_challenge(&body.mfa_token, &body.code)
+        .await?;
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(HttpResponse::Ok(), query.into_inner(), user, tokens);
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Email must be valid"))]
+    pub email: String,
+}
+
+/// Mails a password reset token if the account exists. The response is the same either
+/// way so it can't be used to find out which emails are registered.
+#[post("/auth/password/forgot")]
+pub async fn forgot_password(
+    body: web::Json<ForgotPasswordRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState {
+        repo, auth, mailer, ..
+    } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    match repo.get_user_by_email(body.email.clone()).await {
+        Ok(user) => {
+            let purpose = one_time_token::Purpose::PasswordReset;
+            repo.delete_one_time_tokens(user.id, purpose).await?;
+            let ttl = config::PASSWORD_RESET_TOKEN_TTL_IN_SECONDS;
+            let token = auth.generate_one_time_token(&user, purpose, ttl).await?;
+            mailer
+                .send(Mail::password_reset(&user.email, &token))
+                .await?;
+        }
+        Err(AppError::NotFound(_)) => {}
+        Err(err) => return Err(err),
+    }
+
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
+    pub password: String,
+    #[validate(length(min = 1, message = "Token must not be empty"))]
+    pub token: String,
+}
+
+#[post("/auth/password/reset")]
+pub async fn reset_password(
+    body: web::Json<ResetPasswordRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let purpose = one_time_token::Purpose::PasswordReset;
+    let user_id = auth.redeem_one_time_token(&body.token, purpose).await?;
+    let password = auth.hash_password(&body.password)?;
+    repo.update_user_password(user_id, password).await?;
+    repo.delete_one_time_tokens(user_id, purpose).await?;
+    auth.revoke_user_sessions(user_id).await?;
+
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Token must not be empty"))]
+    pub token: String,
+}
+
+#[post("/auth/verify")]
+pub async fn verify_email(
+    body: web::Json<VerifyEmailRequest>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, auth, .. } = state.get_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let purpose = one_time_token::Purpose::EmailVerification;
+    let user_id = auth.redeem_one_time_token(&body.token, purpose).await?;
+    repo.verify_user_email(user_id).await?;
+    repo.delete_one_time_tokens(user_id, purpose).await?;
+
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+/// Replaces any outstanding verification token with a new one.
+#[post("/auth/verify/resend")]
+pub async fn resend_email_verification(
+    AuthUser(user): AuthUser,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let AppState { repo, .. } = state.get_ref();
+    let user = repo.get_user_by_id(user.id).await?;
+    if user.email_verified_at.is_some() {
+        return Err(AppError::Conflict("Email is already verified".to_string()));
+    }
+
+    send_email_verification(state.get_ref(), &user).await?;
+
+    let res = HttpResponse::Ok().json(json!({"message": "Success"}));
+    Ok(res)
+}
+
+fn access_token_cookie(token: String) -> Cookie<'static> {
+    Cookie::build(config::ACCESS_TOKEN_COOKIE_NAME, token)
+        .http_only(true)
+        .max_age(Duration::seconds(config::ACCESS_TOKEN_TTL_IN_SECONDS))
+        .path("/")
+        .same_site(SameSite::Lax)
+        .secure(config::is_production())
+        .finish()
+}
+
+fn refresh_token_cookie(token: String) -> Cookie<'static> {
+    Cookie::build(config::REFRESH_TOKEN_COOKIE_NAME, token)
+        .http_only(true)
+        .max_age(Duration::seconds(config::REFRESH_TOKEN_TTL_IN_SECONDS))
+        .path(config::REFRESH_TOKEN_COOKIE_PATH)
+        .same_site(SameSite::Strict)
+        .secure(config::is_production())
+        .finish()
+}
+
+fn find_refresh_token(
+    req: &HttpRequest,
+    body: Option<web::Json<RefreshTokenRequest>>,
+) -> Option<String> {
+    match req.cookie(config::REFRESH_TOKEN_COOKIE_NAME) {
+        Some(cookie) => Some(cookie.value().to_string()),
+        None => body.map(|body| body.into_inner().refresh_token),
+    }
+}
+
+async fn send_email_verification(state: &AppState, user: &user::Model) -> Result<(), AppError> {
+    let AppState {
+        repo, auth, mailer, ..
+    } = state;
+    let purpose = one_time_token::Purpose::EmailVerification;
+    repo.delete_one_time_tokens(user.id, purpose).await?;
+    let ttl = config::EMAIL_VERIFICATION_TOKEN_TTL_IN_SECONDS;
+    let token = auth.generate_one_time_token(user, purpose, ttl).await?;
+    mailer
+        .send(Mail::email_verification(&user.email, &token))
+        .await
+}
+
+/// Users with MFA get their tokens from `/auth/mfa/verify` once they answer this.
+pub(super) async fn mfa_challenge(
+    auth: &Auth,
+    user: &user::Model,
+) -> Result<MfaChallengeResponse, AppError> {
+    let purpose = one_time_token::Purpose::MfaChallenge;
+    let ttl = config::MFA_CHALLENGE_TTL_IN_SECONDS;
+    let mfa_token = auth.generate_one_time_token(user, purpose, ttl).await?;
+    Ok(MfaChallengeResponse {
+        expires_in: ttl,
+        mfa_required: true,
+        mfa_token,
+    })
+}
+
+pub(super) fn token_response(
+    mut builder: HttpResponseBuilder,
+    query: TokenQuery,
+    user: user::Model,
+    (access_token, refresh_token): (String, String),
+) -> HttpResponse {
+    builder
+        .cookie(access_token_cookie(access_token.clone()))
+        .cookie(refresh_token_cookie(refresh_token.clone()));
+    let user = user.set_password("".to_string());
+    if !query.include_token {
+        return builder.json(user);
+    }
+
+    builder.json(TokenResponse {
+        access_token,
+        expires_in: config::ACCESS_TOKEN_TTL_IN_SECONDS,
+        refresh_token,
+        token_type: "Bearer".to_string(),
+        user,
+    })
+}
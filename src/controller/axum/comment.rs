@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
+    entity::{comment, error::AppError},
+};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, message = "Body must not be empty"))]
+    body: String,
+    /// The comment on the same post this one replies to.
+    #[serde(default)]
+    parent_id: Option<Uuid>,
+}
+
+pub async fn create_comment(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(post_id): Path<Uuid>,
+    Json(body): Json<CreateCommentRequest>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+    if let Some(parent_id) = body.parent_id {
+        let parent = repo.get_comment_by_id(parent_id).await?;
+        if parent.post_id != post.id {
+            return Err(AppError::NotFound(format!(
+                "Comment '{parent_id}' not found on post '{post_id}'"
+            )));
+        }
+    }
+
+    let mut comment = comment::Model {
+        body: body.body.clone(),
+        parent_id: body.parent_id,
+        post_id: post.id,
+        user_id: user.id,
+        ..Default::default()
+    };
+    policy::require_verified(repo.as_ref(), user.id).await?;
+    policy::authorize(&user, Action::Create, &comment)?;
+    comment = repo.create_comment(comment).await?;
+
+    let res = (StatusCode::CREATED, Json(comment.set_user(user))).into_response();
+    Ok(res)
+}
+
+pub async fn delete_comment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    AuthUser(user): AuthUser,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+
+    let comment = repo.get_comment_by_id(id).await?;
+    policy::authorize(&user, Action::Delete, &comment)?;
+
+    repo.delete_comment_by_id(comment.id).await?;
+    let res = (Json(json!({"message": "Success"}))).into_response();
+    Ok(res)
+}
+
+/// The post's comments and replies, oldest first, to be threaded by `parent_id`.
+pub async fn get_comments(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(post_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    let comments = repo.get_comments(post.id).await?;
+    let res = (Json(comments)).into_response();
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCommentRequest {
+    #[validate(length(min = 1, message = "Body must not be empty"))]
+    body: String,
+}
+
+pub async fn update_comment(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateCommentRequest>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    if let Err(err) = body.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let mut comment = repo.get_comment_by_id(id).await?;
+    policy::authorize(&user, Action::Update, &comment)?;
+    comment = comment::Model {
+        body: body.body.clone(),
+        updated_at: chrono::Utc::now(),
+        ..comment
+    };
+
+    comment = repo.update_comment(comment).await?;
+    let res = (Json(comment)).into_response();
+    Ok(res)
+}
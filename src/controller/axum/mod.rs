@@ -1,5 +1,10 @@
+mod api_key;
+mod comment;
+mod oidc;
 mod others;
 mod post;
+mod reaction;
+mod session;
 mod user;
 
 use std::{net::SocketAddr, sync::Arc, time::Instant};
@@ -10,40 +15,82 @@ use axum::{
     extract::{ConnectInfo, Request},
     middleware::{self, Next},
     response::Response,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use http_body_util::BodyExt;
 
+pub use api_key::*;
+pub use comment::*;
+pub use oidc::*;
 pub use others::*;
 pub use post::*;
+pub use reaction::*;
+pub use session::*;
 pub use user::*;
 
 use crate::{AppState, adapter, config, repository};
 
 #[tokio::main]
 pub async fn main() -> std::io::Result<()> {
+    let repo = repository::new().await.unwrap();
     let state = Arc::new(AppState {
-        repo: repository::new().await.unwrap(),
-        auth: adapter::Auth::new(),
+        auth: adapter::Auth::new(repo.clone()),
+        mailer: adapter::new_mailer(),
+        oidc: adapter::Oidc::from_config(),
+        repo,
     });
     let listener = tokio::net::TcpListener::bind(config::address())
         .await
         .unwrap();
     log::info!("listening on: {}", listener.local_addr().unwrap());
-    axum::serve(listener, new_app(state)).await
+    let app = new_app(state).into_make_service_with_connect_info::<SocketAddr>();
+    axum::serve(listener, app).await
 }
 
 pub fn new_app(state: Arc<AppState>) -> Router {
     Router::new()
-        .route("/auth/me", get(get_auth_user))
+        .route("/.well-known/jwks.json", get(get_jwks))
+        .route("/auth/api-keys", get(get_api_keys).post(create_api_key))
+        .route("/auth/api-keys/{id}", delete(delete_api_key))
+        .route(
+            "/auth/me",
+            get(get_auth_user)
+                .patch(update_auth_user)
+                .delete(delete_auth_user),
+        )
+        .route("/auth/me/password", put(change_password))
+        .route("/auth/mfa", delete(disable_mfa))
+        .route("/auth/mfa/confirm", post(confirm_mfa))
+        .route("/auth/mfa/setup", post(setup_mfa))
+        .route("/auth/mfa/verify", post(verify_mfa))
         .route("/auth/login", post(login_user))
+        .route("/auth/oidc/callback", get(oidc_callback))
+        .route("/auth/oidc/login", get(login_with_oidc))
+        .route("/auth/logout", post(logout_user))
+        .route("/auth/password/forgot", post(forgot_password))
+        .route("/auth/password/reset", post(reset_password))
+        .route("/auth/refresh", post(refresh_access_token))
         .route("/auth/register", post(register_user))
+        .route("/auth/sessions", get(get_sessions))
+        .route("/auth/sessions/{id}", delete(delete_session))
+        .route("/auth/verify", post(verify_email))
+        .route("/auth/verify/resend", post(resend_email_verification))
+        .route("/comments/{id}", delete(delete_comment).put(update_comment))
         .route("/health", get(check_health))
         .route("/posts", post(create_post).get(get_posts))
+        .route("/posts/search", get(search_posts))
         .route(
             "/posts/{id}",
             delete(delete_post).get(get_post).put(update_post),
         )
+        .route(
+            "/posts/{id}/comments",
+            get(get_comments).post(create_comment),
+        )
+        .route(
+            "/posts/{id}/reactions/{kind}",
+            delete(delete_reaction).put(create_reaction),
+        )
         .fallback(not_found)
         .layer(middleware::from_fn(logging_middleware))
         .with_state(state)
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::CookieJar;
+use cookie::{Cookie, SameSite, time::Duration};
+use serde::{Deserialize, Serialize};
+
+use super::{TokenQuery, mfa_challenge, token_response};
+use crate::{
+    AppState,
+    adapter::{AuthorizationRequest, ClientIp, Oidc, UserAgent},
+    config,
+    entity::error::AppError,
+};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct OidcCallbackQuery {
+    #[serde(default)]
+    pub code: String,
+    /// Set instead of `code` when the issuer didn't log the user in.
+    #[serde(default)]
+    pub error: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+/// Starts an OIDC login by sending the browser to the issuer.
+pub async fn login_with_oidc(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+) -> Result<Response, AppError> {
+    let oidc = find_oidc(state.as_ref())?;
+    let discovery = oidc.discover().await?;
+    let request = AuthorizationRequest::new();
+    let url = oidc.authorization_url(&discovery, &request)?;
+
+    let jar = jar.add(oidc_cookie(request.to_cookie_value()));
+    Ok((jar, Redirect::to(&url)).into_response())
+}
+
+/// Where the issuer sends the browser back to. Logs in the account with the ID token's
+/// verified email, creating it on first use, like `login_user` would.
+pub async fn oidc_callback(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Response, AppError> {
+    let AppState { auth, .. } = state.as_ref();
+    let oidc = find_oidc(state.as_ref())?;
+    let request = jar
+        .get(config::OIDC_COOKIE_NAME)
+        .and_then(|cookie| AuthorizationRequest::from_cookie_value(cookie.value()))
+        .filter(|request| request.state == query.state);
+    let Some(request) = request else {
+        return Err(AppError::Unauthorized(
+            "Login state is missing or doesn't match".to_string(),
+        ));
+    };
+    if !query.error.is_empty() {
+        return Err(AppError::Unauthorized(format!(
+            "Identity provider returned '{}'",
+            query.error
+        )));
+    }
+
+    let discovery = oidc.discover().await?;
+    let claims = oidc
+        .exchange_code(&discovery, &query.code, &request)
+        .await?;
+    let user = auth.find_or_create_oidc_user(&claims).await?;
+    let jar = jar.remove(Cookie::build(config::OIDC_COOKIE_NAME).path(config::OIDC_COOKIE_PATH));
+    if user.mfa_enabled_at.is_some() {
+        let body = mfa_challenge(auth, &user).await?;
+        return Ok((StatusCode::OK, jar, Json(body)).into_response());
+    }
+
+    let tokens = auth.start_session(&user, ip, user_agent).await?;
+    let res = token_response(StatusCode::OK, jar, TokenQuery::default(), user, tokens);
+    Ok(res)
+}
+
+fn find_oidc(state: &AppState) -> Result<&Oidc, AppError> {
+    match &state.oidc {
+        Some(oidc) => Ok(oidc),
+        None => Err(AppError::NotFound(
+            "OIDC login is not configured".to_string(),
+        )),
+    }
+}
+
+/// `Lax` so it is sent along when the issuer redirects back.
+fn oidc_cookie(value: String) -> Cookie<'static> {
+    Cookie::build((config::OIDC_COOKIE_NAME, value))
+        .http_only(true)
+        .max_age(Duration::seconds(config::OIDC_LOGIN_TTL_IN_SECONDS))
+        .path(config::OIDC_COOKIE_PATH)
+        .same_site(SameSite::Lax)
+        .secure(config::is_production())
+        .build()
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     Json,
     extract::State,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde_json::json;
@@ -16,6 +16,13 @@ pub async fn check_health(State(state): State<Arc<AppState>>) -> Result<Response
     Ok(res)
 }
 
+/// Public keys for verifying access tokens, cached briefly so rotations propagate.
+pub async fn get_jwks(State(state): State<Arc<AppState>>) -> Result<Response, AppError> {
+    let headers = [(header::CACHE_CONTROL, "public, max-age=300")];
+    let res = (StatusCode::OK, headers, Json(state.auth.jwks())).into_response();
+    Ok(res)
+}
+
 pub async fn not_found() -> Result<Response, AppError> {
     Err(AppError::NotFound("Route doesn't exist".to_string()))
 }
@@ -3,10 +3,9 @@ use std::sync::Arc;
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
-use axum_extra::extract::CookieJar;
 use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
@@ -14,6 +13,10 @@ use validator::Validate;
 
 use crate::{
     AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
     entity::{error::AppError, post},
 };
 
@@ -27,11 +30,10 @@ pub struct CreatePostRequest {
 
 pub async fn create_post(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    AuthUser(user): AuthUser,
     Json(body): Json<CreatePostRequest>,
 ) -> Result<Response, AppError> {
-    let AppState { repo, auth } = state.as_ref();
-    let user = auth.extract_user_from_axum(jar)?;
+    let AppState { repo, .. } = state.as_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
@@ -42,6 +44,8 @@ pub async fn create_post(
         user_id: user.id,
         ..Default::default()
     };
+    policy::require_verified(repo.as_ref(), user.id).await?;
+    policy::authorize(&user, Action::Create, &post)?;
     post = repo.create_post(post).await?;
 
     let res = (StatusCode::CREATED, Json(post.set_user(user))).into_response();
@@ -51,15 +55,12 @@ pub async fn create_post(
 pub async fn delete_post(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-    jar: CookieJar,
+    AuthUser(user): AuthUser,
 ) -> Result<Response, AppError> {
-    let AppState { repo, auth } = state.as_ref();
-    let user = auth.extract_user_from_axum(jar)?;
+    let AppState { repo, .. } = state.as_ref();
 
     let post = repo.get_post_by_id(id).await?;
-    if post.user.unwrap().id != user.id {
-        return Err(AppError::Forbidden("You can't delete this post".to_string()));
-    }
+    policy::authorize(&user, Action::Delete, &post)?;
 
     repo.delete_post_by_id(post.id).await?;
     let res = (Json(json!({"message": "Success"}).to_string())).into_response();
@@ -68,28 +69,63 @@ pub async fn delete_post(
 
 pub async fn get_posts(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    AuthUser(user): AuthUser,
     Query(query): Query<post::Pagination>,
 ) -> Result<Response, AppError> {
-    let AppState { repo, auth } = state.as_ref();
-    auth.extract_user_from_axum(jar)?;
+    let AppState { repo, .. } = state.as_ref();
+    if let Err(err) = query.validate() {
+        return Err(AppError::Validation(err));
+    }
+
+    let (posts, total) = repo.get_posts(query.with_lookahead()).await?;
+    let mut page = post::Page::new(posts, total, &query);
+    let ids = page.items.iter().map(|post| post.id).collect();
+    let mut summaries = repo.get_reaction_summaries(ids, user.id).await?;
+    for post in page.items.iter_mut() {
+        post.reactions = Some(summaries.remove(&post.id).unwrap_or_default());
+    }
+    let headers = page
+        .links("/posts", &query)
+        .map(|links| [(header::LINK, links)]);
+    let res = (headers, Json(page)).into_response();
+    Ok(res)
+}
+
+/// Posts matching every word of `q` in their title or content, best matches first.
+pub async fn search_posts(
+    State(state): State<Arc<AppState>>,
+    _: AuthUser,
+    Query(query): Query<post::SearchQuery>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
     if let Err(err) = query.validate() {
         return Err(AppError::Validation(err));
     }
+    if let Err(err) = query.pagination().validate() {
+        return Err(AppError::Validation(err));
+    }
 
-    let posts = repo.get_posts(query).await?;
-    let res = (Json(posts)).into_response();
+    let (items, total) = repo.search_posts(query.clone()).await?;
+    let results = post::SearchResults {
+        items,
+        limit: query.limit,
+        offset: query.offset,
+        total,
+    };
+    let res = (Json(results)).into_response();
     Ok(res)
 }
 
 pub async fn get_post(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    AuthUser(user): AuthUser,
     Path(id): Path<Uuid>,
 ) -> Result<Response, AppError> {
-    let AppState { repo, auth } = state.as_ref();
-    auth.extract_user_from_axum(jar)?;
-    let post = repo.get_post_by_id(id).await?;
+    let AppState { repo, .. } = state.as_ref();
+    let mut post = repo.get_post_by_id(id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    post.reactions = Some(summaries.remove(&post.id).unwrap_or_default());
 
     let res = (Json(post)).into_response();
     Ok(res)
@@ -105,17 +141,17 @@ pub struct UpdatePostRequest {
 
 pub async fn update_post(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    AuthUser(user): AuthUser,
     Path(id): Path<Uuid>,
     Json(body): Json<UpdatePostRequest>,
 ) -> Result<Response, AppError> {
-    let AppState { repo, auth } = state.as_ref();
-    let user = auth.extract_user_from_axum(jar)?;
+    let AppState { repo, .. } = state.as_ref();
     if let Err(err) = body.validate() {
         return Err(AppError::Validation(err));
     }
 
     let mut post = repo.get_post_by_id(id).await?;
+    policy::authorize(&user, Action::Update, &post)?;
     post = post::Model {
         content: body.content.clone(),
         title: body.title.clone(),
@@ -123,10 +159,6 @@ pub async fn update_post(
         ..post
     };
 
-    if post.user.clone().unwrap().id != user.id {
-        return Err(AppError::Forbidden("You can't update this post".to_string()));
-    }
-
     post = repo.update_post(post).await?;
     let res = (Json(post)).into_response();
     Ok(res)
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    adapter::{
+        AuthUser,
+        policy::{self, Action},
+    },
+    entity::{error::AppError, reaction},
+};
+
+/// Reacts to the post, returning its reactions. Reacting twice with the same kind is a
+/// no-op.
+pub async fn create_reaction(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path((post_id, kind)): Path<(Uuid, String)>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    let kind: reaction::Kind = kind.parse().map_err(AppError::NotFound)?;
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    let reaction = reaction::Model {
+        kind,
+        post_id: post.id,
+        user_id: user.id,
+        ..Default::default()
+    };
+    repo.create_reaction(reaction).await?;
+
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    let res = (Json(summaries.remove(&post.id).unwrap_or_default())).into_response();
+    Ok(res)
+}
+
+/// Takes back the reaction, returning the post's reactions. Succeeds if there was none.
+pub async fn delete_reaction(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path((post_id, kind)): Path<(Uuid, String)>,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    let kind: reaction::Kind = kind.parse().map_err(AppError::NotFound)?;
+    let post = repo.get_post_by_id(post_id).await?;
+    policy::authorize(&user, Action::Read, &post)?;
+
+    repo.delete_reaction(post.id, user.id, kind).await?;
+
+    let mut summaries = repo.get_reaction_summaries(vec![post.id], user.id).await?;
+    let res = (Json(summaries.remove(&post.id).unwrap_or_default())).into_response();
+    Ok(res)
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    adapter::AuthUser,
+    entity::{error::AppError, session},
+};
+
+/// A session as its user sees it, `expires_at` as a plain timestamp rather than the
+/// BSON date it is stored as.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub last_seen_at: DateTime<Utc>,
+    pub user_agent: String,
+}
+
+impl From<session::Model> for SessionResponse {
+    fn from(session: session::Model) -> Self {
+        SessionResponse {
+            id: session.id,
+            created_at: session.created_at,
+            expires_at: session.expires_at,
+            ip: session.ip,
+            last_seen_at: session.last_seen_at,
+            user_agent: session.user_agent,
+        }
+    }
+}
+
+/// Signs the session out, whichever device it is on.
+pub async fn delete_session(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    AuthUser(user): AuthUser,
+) -> Result<Response, AppError> {
+    let AppState { auth, .. } = state.as_ref();
+    auth.revoke_session(id, user.id).await?;
+    let res = (StatusCode::OK, Json(json!({"message": "Success"}))).into_response();
+    Ok(res)
+}
+
+pub async fn get_sessions(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+) -> Result<Response, AppError> {
+    let AppState { repo, .. } = state.as_ref();
+    let sessions: Vec<SessionResponse> = repo
+        .get_sessions(user.id)
+        .await?
+        .into_iter()
+        .map(SessionResponse::from)
+        .collect();
+    let res = (StatusCode::OK, Json(sessions)).into_response();
+    Ok(res)
+}
@@ -0,0 +1,56 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{entity::user::Role, repository};
+
+const USAGE: &str = "Usage: rust-rest-api migrate [up [steps] | down [steps] | fresh | status]";
+const ROLE_USAGE: &str = "Usage: rust-rest-api role <email> <admin | user>";
+
+/// Applies or rolls back schema migrations on the configured database. `up` applies every
+/// pending migration unless `steps` is given, `down` rolls back one unless `steps` is given.
+#[tokio::main]
+pub async fn migrate(args: &[String]) -> std::io::Result<()> {
+    let steps = match args.get(1).map(|arg| arg.parse::<u32>()) {
+        None => None,
+        Some(Ok(steps)) => Some(steps),
+        Some(Err(_)) => return Err(Error::new(ErrorKind::InvalidInput, USAGE)),
+    };
+
+    let repo = repository::connect().await.map_err(Error::other)?;
+    let result = match args.first().map_or("up", String::as_str) {
+        "down" => repo.migrate_down(Some(steps.unwrap_or(1))).await,
+        "fresh" => match repo.migrate_down(None).await {
+            Ok(_) => repo.migrate_up(None).await,
+            Err(err) => Err(err),
+        },
+        "status" => repo.migration_status().await.map(|statuses| {
+            for status in statuses {
+                let state = if status.applied { "Applied" } else { "Pending" };
+                println!("{state}\t{}", status.name);
+            }
+        }),
+        "up" => repo.migrate_up(steps).await,
+        _ => return Err(Error::new(ErrorKind::InvalidInput, USAGE)),
+    };
+    result.map_err(Error::other)
+}
+
+/// Assigns a role to the user with the given email. Access tokens issued before the change
+/// keep the old role until they expire.
+#[tokio::main]
+pub async fn role(args: &[String]) -> std::io::Result<()> {
+    let (Some(email), Some(Ok(role))) = (args.first(), args.get(1).map(|arg| arg.parse::<Role>()))
+    else {
+        return Err(Error::new(ErrorKind::InvalidInput, ROLE_USAGE));
+    };
+
+    let repo = repository::connect().await.map_err(Error::other)?;
+    let user = repo
+        .get_user_by_email(email.to_string())
+        .await
+        .map_err(Error::other)?;
+    repo.update_user_role(user.id, role)
+        .await
+        .map_err(Error::other)?;
+    println!("{role}\t{email}");
+    Ok(())
+}
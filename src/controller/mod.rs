@@ -1,2 +1,3 @@
 pub mod actix;
 pub mod axum;
+pub mod cli;
@@ -0,0 +1,115 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use validator::{ValidationError, ValidationErrors};
+
+/// A personal API key that scripts send as `Authorization: Bearer <key>` instead of
+/// logging in. Only the hash is stored, `prefix` is the start of the key so users can
+/// tell their keys apart.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "api_keys")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Keys without one are valid until they are revoked.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[sea_orm(unique)]
+    pub key_hash: String,
+    pub name: String,
+    pub prefix: String,
+    pub scope: Scope,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+/// What requests a key can authenticate.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Only `GET`, `HEAD` and `OPTIONS` requests.
+    #[sea_orm(string_value = "read_only")]
+    ReadOnly,
+    #[default]
+    #[sea_orm(string_value = "read_write")]
+    ReadWrite,
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::ReadOnly => write!(f, "read_only"),
+            Scope::ReadWrite => write!(f, "read_write"),
+        }
+    }
+}
+
+impl Model {
+    pub fn set_key_hash(mut self, value: String) -> Self {
+        self.key_hash = value;
+        self
+    }
+}
+
+/// When a key created now expires, `expires_in` seconds from now.
+pub fn expires_at(
+    expires_in: Option<i64>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, ValidationErrors> {
+    let Some(seconds) = expires_in else {
+        return Ok(None);
+    };
+    match chrono::TimeDelta::try_seconds(seconds)
+        .and_then(|delta| chrono::Utc::now().checked_add_signed(delta))
+    {
+        Some(at) => Ok(Some(at)),
+        None => {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "expires_in",
+                ValidationError::new("range").with_message("Expiry is out of range".into()),
+            );
+            Err(errors)
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            created_at: chrono::Utc::now(),
+            expires_at: None,
+            key_hash: String::new(),
+            name: String::new(),
+            prefix: String::new(),
+            scope: Scope::default(),
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
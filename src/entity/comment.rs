@@ -0,0 +1,87 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+use crate::entity::user;
+
+/// A comment on a post, or a reply to another comment on the same post when it has a
+/// `parent_id`. Replies go with the comment they answer when it is deleted.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "comments")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    #[sea_orm(indexed)]
+    pub parent_id: Option<Uuid>,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub post_id: Uuid,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sea_orm(ignore)]
+    pub user: Option<user::Model>,
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default, skip_serializing_if = "Uuid::is_nil")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+impl Model {
+    pub fn set_user(mut self, value: user::Model) -> Self {
+        self.user = Some(value);
+        self.user_id = Uuid::nil();
+        self
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            body: String::new(),
+            created_at: chrono::Utc::now(),
+            parent_id: None,
+            post_id: Uuid::default(),
+            updated_at: chrono::Utc::now(),
+            user: None,
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::post::Entity",
+        from = "Column::PostId",
+        to = "super::post::Column::Id"
+    )]
+    Post,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Post.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
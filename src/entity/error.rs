@@ -22,6 +22,9 @@ pub enum AppError {
     Internal { err: String, path: String },
     #[error("NotFound: {0}")]
     NotFound(String),
+    /// `retry_after` is in seconds and sent as the `Retry-After` header.
+    #[error("TooManyRequests: {msg}, retry after {retry_after}s")]
+    TooManyRequests { msg: String, retry_after: i64 },
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
     #[error("Validation: {0}")]
@@ -39,6 +42,7 @@ pub struct AppErrorResponse {
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let mut details = json!(null);
+        let mut retry_after = None;
         let status: AxumStatusCode;
         let message: String;
         match self {
@@ -59,6 +63,14 @@ impl IntoResponse for AppError {
                 status = AxumStatusCode::NOT_FOUND;
                 message = msg;
             }
+            AppError::TooManyRequests {
+                msg,
+                retry_after: seconds,
+            } => {
+                status = AxumStatusCode::TOO_MANY_REQUESTS;
+                message = msg;
+                retry_after = Some(seconds.to_string());
+            }
             AppError::Unauthorized(msg) => {
                 status = AxumStatusCode::UNAUTHORIZED;
                 message = msg;
@@ -80,13 +92,20 @@ impl IntoResponse for AppError {
             status: status.as_u16(),
             details,
         };
-        (status, axum::Json(body)).into_response()
+        match retry_after {
+            Some(seconds) => {
+                let headers = [(axum::http::header::RETRY_AFTER, seconds)];
+                (status, headers, axum::Json(body)).into_response()
+            }
+            None => (status, axum::Json(body)).into_response(),
+        }
     }
 }
 
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
         let mut details = json!(null);
+        let mut retry_after = None;
         let message: String;
         let status: ActixStatusCode;
         match self {
@@ -107,6 +126,14 @@ impl ResponseError for AppError {
                 message = msg.to_string();
                 status = ActixStatusCode::NOT_FOUND;
             }
+            AppError::TooManyRequests {
+                msg,
+                retry_after: seconds,
+            } => {
+                message = msg.to_string();
+                retry_after = Some(seconds.to_string());
+                status = ActixStatusCode::TOO_MANY_REQUESTS;
+            }
             AppError::Unauthorized(msg) => {
                 message = msg.to_string();
                 status = ActixStatusCode::UNAUTHORIZED;
@@ -128,7 +155,11 @@ impl ResponseError for AppError {
             status: status.as_u16(),
             details,
         };
-        HttpResponse::build(status).json(body)
+        let mut res = HttpResponse::build(status);
+        if let Some(seconds) = retry_after {
+            res.insert_header((actix_web::http::header::RETRY_AFTER, seconds));
+        }
+        res.json(body)
     }
 }
 
@@ -155,6 +186,8 @@ impl From<mongodb::error::Error> for AppError {
 
 impl From<sea_orm::DbErr> for AppError {
     fn from(err: sea_orm::DbErr) -> Self {
+        // `sql_err` normalises Postgres (23505) and SQLite (1555, 2067) unique violations,
+        // and both name the offending table in the message.
         if let Some(sql_err) = err.sql_err()
             && let SqlErr::UniqueConstraintViolation(msg) = sql_err
             && msg.contains(config::COLLECTION_USERS)
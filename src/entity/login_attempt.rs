@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// Failed logins for an account or a client IP address, counted until `expires_at`
+/// passes without another failure.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "login_attempts")]
+pub struct Model {
+    /// `email:<email>`, `ip:<address>` or `mfa:<user id>`, see `Model::email_key`,
+    /// `Model::ip_key` and `Model::mfa_key`.
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub failures: i32,
+    pub last_failed_at: chrono::DateTime<chrono::Utc>,
+    /// Stored as a BSON date so Mongo's TTL index can expire it.
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    #[sea_orm(indexed)]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn email_key(email: &str) -> String {
+        format!("email:{}", email.to_lowercase())
+    }
+
+    pub fn ip_key(ip: std::net::IpAddr) -> String {
+        format!("ip:{ip}")
+    }
+
+    pub fn mfa_key(user_id: Uuid) -> String {
+        format!("mfa:{user_id}")
+    }
+
+    /// When the lockout earned by these failures ends, `None` if `max_failures` hasn't
+    /// been reached. Each failure past it doubles the lockout.
+    pub fn locked_until(&self, max_failures: i32) -> Option<chrono::DateTime<chrono::Utc>> {
+        if self.failures < max_failures {
+            return None;
+        }
+
+        let doublings = (self.failures - max_failures).min(16) as u32;
+        let seconds = (config::LOGIN_LOCKOUT_BASE_IN_SECONDS << doublings)
+            .min(config::LOGIN_LOCKOUT_MAX_IN_SECONDS);
+        Some(self.last_failed_at + chrono::Duration::seconds(seconds))
+    }
+}
@@ -1,3 +1,11 @@
+pub mod api_key;
+pub mod comment;
 pub mod error;
+pub mod login_attempt;
+pub mod one_time_token;
 pub mod post;
+pub mod reaction;
+pub mod refresh_token;
+pub mod revoked_token;
+pub mod session;
 pub mod user;
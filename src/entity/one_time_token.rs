@@ -0,0 +1,84 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// A token that can be used once, e.g. mailed to a user to prove they control their email
+/// address, or a 2FA recovery code. Only the hash is stored.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "one_time_tokens")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub purpose: Purpose,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+/// What a token can be redeemed for, a token issued for one purpose is rejected by the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "snake_case")]
+pub enum Purpose {
+    #[sea_orm(string_value = "email_verification")]
+    EmailVerification,
+    /// Issued after the password is verified for users with MFA, exchanged for access
+    /// and refresh tokens along with a TOTP or recovery code.
+    #[sea_orm(string_value = "mfa_challenge")]
+    MfaChallenge,
+    #[sea_orm(string_value = "mfa_recovery")]
+    MfaRecovery,
+    #[sea_orm(string_value = "password_reset")]
+    PasswordReset,
+}
+
+impl std::fmt::Display for Purpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Purpose::EmailVerification => write!(f, "email_verification"),
+            Purpose::MfaChallenge => write!(f, "mfa_challenge"),
+            Purpose::MfaRecovery => write!(f, "mfa_recovery"),
+            Purpose::PasswordReset => write!(f, "password_reset"),
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            created_at: chrono::Utc::now(),
+            expires_at: chrono::Utc::now(),
+            purpose: Purpose::PasswordReset,
+            token_hash: String::new(),
+            used_at: None,
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,9 +1,12 @@
+use std::{fmt, str::FromStr};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-use crate::entity::user;
+use crate::entity::{reaction, user};
 
 #[serde_as]
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
@@ -15,6 +18,10 @@ pub struct Model {
     pub id: Uuid,
     pub content: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Only set in responses, by the controllers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sea_orm(ignore)]
+    pub reactions: Option<reaction::Summary>,
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[sea_orm(ignore)]
@@ -30,7 +37,7 @@ impl Model {
     pub fn set_user(mut self, value: user::Model) -> Self {
         self.user = Some(value);
         self.user_id = Uuid::nil();
-        return self;
+        self
     }
 }
 
@@ -40,6 +47,7 @@ impl Default for Model {
             id: Uuid::now_v7(),
             created_at: chrono::Utc::now(),
             content: String::new(),
+            reactions: None,
             title: String::new(),
             updated_at: chrono::Utc::now(),
             user: None,
@@ -66,11 +74,473 @@ impl Related<super::user::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
-#[derive(Clone, Copy, Debug, Deserialize, Validate)]
+/// An opaque position in a listing. Post IDs are UUIDv7, so they sort by creation and
+/// the cursor is simply the ID, base64url-encoded so clients don't rely on that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor(pub Uuid);
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&URL_SAFE_NO_PAD.encode(self.0.as_bytes()))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(value)
+            .map_err(|_| "Cursor is invalid".to_string())?;
+        let id = Uuid::from_slice(&bytes).map_err(|_| "Cursor is invalid".to_string())?;
+        Ok(Cursor(id))
+    }
+}
+
+/// What a post listing is narrowed down to. Every repository applies it, the in-memory
+/// one through `matches`. Date bounds are exclusive and `title_prefix` ignores case.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Validate)]
+#[validate(schema(function = "validate_filter"))]
+pub struct Filter {
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    #[validate(length(
+        min = 1,
+        max = 200,
+        message = "Title prefix must be 1 to 200 characters"
+    ))]
+    pub title_prefix: Option<String>,
+    pub updated_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub user_id: Option<Uuid>,
+}
+
+impl Filter {
+    pub fn matches(&self, post: &Model) -> bool {
+        self.created_after.is_none_or(|at| post.created_at > at)
+            && self.created_before.is_none_or(|at| post.created_at < at)
+            && self.title_prefix.as_ref().is_none_or(|prefix| {
+                post.title
+                    .to_lowercase()
+                    .starts_with(&prefix.to_lowercase())
+            })
+            && self.updated_after.is_none_or(|at| post.updated_at > at)
+            && self.updated_before.is_none_or(|at| post.updated_at < at)
+            && self.user_id.is_none_or(|user_id| post.user_id == user_id)
+    }
+}
+
+fn validate_filter(filter: &Filter) -> Result<(), ValidationError> {
+    let ranges = [
+        (filter.created_after, filter.created_before),
+        (filter.updated_after, filter.updated_before),
+    ];
+    if ranges
+        .iter()
+        .any(|range| matches!(range, (Some(after), Some(before)) if after >= before))
+    {
+        return Err(ValidationError::new("range")
+            .with_message("Date ranges must end after they start".into()));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
+    CreatedAt,
+    Title,
+    UpdatedAt,
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortField::CreatedAt => write!(f, "created_at"),
+            SortField::Title => write!(f, "title"),
+            SortField::UpdatedAt => write!(f, "updated_at"),
+        }
+    }
+}
+
+/// A listing order, written `created_at`, `updated_at` or `title` followed by `:asc`
+/// (the default) or `:desc`. Posts that tie are ordered by ID in the same direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sort {
+    pub descending: bool,
+    pub field: SortField,
+}
+
+impl Sort {
+    pub fn compare(&self, a: &Model, b: &Model) -> std::cmp::Ordering {
+        let order = match self.field {
+            SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            // Lowercased like the databases order titles, so case doesn't split them.
+            SortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+        }
+        .then(a.id.cmp(&b.id));
+        if self.descending {
+            return order.reverse();
+        }
+        order
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.descending { "desc" } else { "asc" };
+        write!(f, "{}:{direction}", self.field)
+    }
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = value.split_once(':').unwrap_or((value, "asc"));
+        let field = match field {
+            "created_at" => SortField::CreatedAt,
+            "title" => SortField::Title,
+            "updated_at" => SortField::UpdatedAt,
+            _ => return Err(format!("Sort field '{field}' is not supported")),
+        };
+        let descending = match direction {
+            "asc" => false,
+            "desc" => true,
+            _ => return Err(format!("Sort direction '{direction}' is not supported")),
+        };
+        Ok(Sort { descending, field })
+    }
+}
+
+/// Posts are listed newest first unless `sort` says otherwise, either `offset` posts in
+/// or next to a cursor. Cursors only work with the default order.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, Validate)]
+#[validate(schema(function = "validate_pagination"))]
 pub struct Pagination {
+    /// Only posts older than this one.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub after: Option<Cursor>,
+    /// Only the posts newer than this one that come right before it.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub before: Option<Cursor>,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub filter: Filter,
     #[validate(range(min = 1, max = 50))]
     pub limit: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
     #[validate(range(min = 0))]
     pub offset: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub sort: Option<Sort>,
+}
+
+impl Pagination {
+    /// Asks for one post more than the page holds, which tells `Page::new` whether
+    /// another page follows.
+    pub fn with_lookahead(&self) -> Self {
+        Pagination {
+            limit: self.limit + 1,
+            ..self.clone()
+        }
+    }
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn validate_pagination(pagination: &Pagination) -> Result<(), ValidationError> {
+    let has_cursor = pagination.after.is_some() || pagination.before.is_some();
+    if pagination.after.is_some() && pagination.before.is_some() {
+        return Err(ValidationError::new("cursor")
+            .with_message("Only one of after and before can be set".into()));
+    }
+    if has_cursor && pagination.offset > 0 {
+        return Err(ValidationError::new("cursor")
+            .with_message("Offset can't be combined with a cursor".into()));
+    }
+    if has_cursor && pagination.sort.is_some() {
+        return Err(ValidationError::new("cursor")
+            .with_message("Sort can't be combined with a cursor".into()));
+    }
+    Ok(())
+}
+
+/// A page of posts. `next_cursor` is passed as `after` to get the page that follows and
+/// `prev_cursor` as `before` to get the one that precedes it, each only set when there is
+/// one. `total` counts every post in the listing, not only those past the cursor.
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Page {
+    pub items: Vec<Model>,
+    pub limit: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub next_cursor: Option<Cursor>,
+    pub offset: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub prev_cursor: Option<Cursor>,
+    pub total: u64,
+}
+
+impl Page {
+    /// Builds the page from the posts found for `pagination.with_lookahead()`.
+    pub fn new(mut posts: Vec<Model>, total: u64, pagination: &Pagination) -> Self {
+        let has_more = posts.len() as u64 > pagination.limit;
+        if has_more && pagination.before.is_some() {
+            // The extra post is the newest one, past the start of the page.
+            posts.remove(0);
+        } else if has_more {
+            posts.pop();
+        }
+
+        // Everything before a cursor is followed by at least the post it points at, and
+        // everything after one is preceded by it.
+        let next_cursor = if has_more || pagination.before.is_some() {
+            posts.last().map(|post| Cursor(post.id))
+        } else {
+            None
+        };
+        let prev_cursor = if (has_more && pagination.before.is_some()) || pagination.after.is_some()
+        {
+            posts.first().map(|post| Cursor(post.id))
+        } else {
+            None
+        };
+        Page {
+            items: posts,
+            limit: pagination.limit,
+            next_cursor,
+            offset: pagination.offset,
+            prev_cursor,
+            total,
+        }
+    }
+
+    /// The RFC 8288 `Link` header value pointing at the next and previous pages of
+    /// `path`, by cursor when the page was fetched by one and by offset otherwise.
+    pub fn links(&self, path: &str, pagination: &Pagination) -> Option<String> {
+        let at = |after, before, offset| Pagination {
+            after,
+            before,
+            offset,
+            ..pagination.clone()
+        };
+        let (next, prev) = if pagination.after.is_some() || pagination.before.is_some() {
+            (
+                self.next_cursor.map(|cursor| at(Some(cursor), None, 0)),
+                self.prev_cursor.map(|cursor| at(None, Some(cursor), 0)),
+            )
+        } else {
+            (
+                self.next_cursor
+                    .map(|_| at(None, None, self.offset + self.limit)),
+                (self.offset > 0).then(|| at(None, None, self.offset.saturating_sub(self.limit))),
+            )
+        };
+        let links: Vec<String> = [(next, "next"), (prev, "prev")]
+            .into_iter()
+            .filter_map(|(pagination, rel)| {
+                let query = serde_urlencoded::to_string(pagination?).ok()?;
+                Some(format!("<{path}?{query}>; rel=\"{rel}\""))
+            })
+            .collect();
+        if links.is_empty() {
+            return None;
+        }
+        Some(links.join(", "))
+    }
+}
+
+/// About as many words as Postgres' `ts_headline` puts in a snippet.
+const SNIPPET_WORDS: usize = 30;
+/// Put around matches instead of `<mark>` until the rest of a snippet has been escaped.
+pub const SNIPPET_START: char = '\u{2}';
+pub const SNIPPET_STOP: char = '\u{3}';
+
+/// `GET /posts/search` takes the same `limit`, `offset` and `user_id` as listings.
+#[derive(Clone, Debug, Deserialize, Validate)]
+pub struct SearchQuery {
+    #[validate(length(min = 1, max = 200, message = "Query must be 1 to 200 characters"))]
+    pub q: String,
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
     pub user_id: Option<Uuid>,
 }
+
+impl SearchQuery {
+    /// Validated like a listing so searches share its limits.
+    pub fn pagination(&self) -> Pagination {
+        Pagination {
+            after: None,
+            before: None,
+            filter: Filter {
+                user_id: self.user_id,
+                ..Default::default()
+            },
+            limit: self.limit,
+            offset: self.offset,
+            sort: None,
+        }
+    }
+
+    /// The lowercased words of `q`.
+    pub fn terms(&self) -> Vec<String> {
+        self.q
+            .split(is_separator)
+            .filter(|term| !term.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+/// A post matching a search, best matches having the highest `rank`. `snippet` is an
+/// HTML-escaped excerpt of its content with the matching words wrapped in `<mark>`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub post: Model,
+    pub rank: f64,
+    #[serde(default)]
+    pub snippet: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SearchResults {
+    pub items: Vec<SearchResult>,
+    pub limit: u64,
+    pub offset: u64,
+    pub total: u64,
+}
+
+/// Ranks `posts` against `query` for the repositories without a full-text index. Every
+/// term has to match the start of a word, which roughly stands in for stemming, and
+/// matches in the title count twice. Returns the requested page and the number of matches.
+pub fn search(
+    posts: impl IntoIterator<Item = Model>,
+    query: &SearchQuery,
+) -> (Vec<SearchResult>, u64) {
+    let terms = query.terms();
+    if terms.is_empty() {
+        return (vec![], 0);
+    }
+    let mut results: Vec<SearchResult> = posts
+        .into_iter()
+        .filter_map(|post| {
+            let mut rank = 0.0;
+            for term in &terms {
+                let hits =
+                    2 * count_matches(&post.title, term) + count_matches(&post.content, term);
+                if hits == 0 {
+                    return None;
+                }
+                rank += hits as f64;
+            }
+            let snippet = search_snippet(&post.content, &terms);
+            Some(SearchResult {
+                post,
+                rank,
+                snippet,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.post.id.cmp(&a.post.id)));
+
+    let total = results.len() as u64;
+    let results = results
+        .into_iter()
+        .skip(query.offset as usize)
+        .take(query.limit as usize)
+        .collect();
+    (results, total)
+}
+
+/// The words of `text` around the first one matching `terms`, matches wrapped in `<mark>`
+/// like `ts_headline` does.
+pub fn search_snippet(text: &str, terms: &[String]) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let first = words
+        .iter()
+        .position(|word| {
+            word.split(is_separator)
+                .any(|part| matches_any(part, terms))
+        })
+        .unwrap_or(0);
+    let snippet = words
+        .iter()
+        .skip(first.saturating_sub(SNIPPET_WORDS / 4))
+        .take(SNIPPET_WORDS)
+        .map(|word| mark_terms(word, terms))
+        .collect::<Vec<_>>()
+        .join(" ");
+    mark_snippet(&snippet)
+}
+
+/// Wraps the parts of `word` that match in `SNIPPET_START` and `SNIPPET_STOP`. It is split
+/// the way the query is, so `rust-lang` has `rust` marked like it is counted.
+fn mark_terms(word: &str, terms: &[String]) -> String {
+    let mut marked = String::with_capacity(word.len());
+    for part in word.split_inclusive(is_separator) {
+        let (part, separator) = part.split_at(part.trim_end_matches(is_separator).len());
+        if matches_any(part, terms) {
+            marked.push(SNIPPET_START);
+            marked.push_str(part);
+            marked.push(SNIPPET_STOP);
+        } else {
+            marked.push_str(part);
+        }
+        marked.push_str(separator);
+    }
+    marked
+}
+
+/// Escapes `snippet` so it can be shown as HTML, then swaps the `SNIPPET_START` and
+/// `SNIPPET_STOP` around matches for `<mark>` tags. Stray markers, which could come from
+/// the content itself, are dropped so the tags always pair up.
+pub fn mark_snippet(snippet: &str) -> String {
+    let mut marked = String::with_capacity(snippet.len());
+    let mut open = false;
+    for c in snippet.chars() {
+        match c {
+            SNIPPET_START if !open => {
+                marked.push_str("<mark>");
+                open = true;
+            }
+            SNIPPET_STOP if open => {
+                marked.push_str("</mark>");
+                open = false;
+            }
+            SNIPPET_START | SNIPPET_STOP => {}
+            '&' => marked.push_str("&amp;"),
+            '<' => marked.push_str("&lt;"),
+            '>' => marked.push_str("&gt;"),
+            '"' => marked.push_str("&quot;"),
+            '\'' => marked.push_str("&#39;"),
+            c => marked.push(c),
+        }
+    }
+    if open {
+        marked.push_str("</mark>");
+    }
+    marked
+}
+
+fn count_matches(text: &str, term: &str) -> usize {
+    text.split(is_separator)
+        .filter(|word| word.to_lowercase().starts_with(term))
+        .count()
+}
+
+/// Whether `word`, a part between separators, starts with any of the terms.
+fn matches_any(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    !word.is_empty() && terms.iter().any(|term| word.starts_with(term.as_str()))
+}
+
+/// What queries and content are split into words at, anything but letters and digits.
+fn is_separator(c: char) -> bool {
+    !c.is_alphanumeric()
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// A user's reaction to a post. Users can react with several kinds, but only once each.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "reactions")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub kind: Kind,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub post_id: Uuid,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            created_at: chrono::Utc::now(),
+            kind: Kind::default(),
+            post_id: Uuid::default(),
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::post::Entity",
+        from = "Column::PostId",
+        to = "super::post::Column::Id"
+    )]
+    Post,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::post::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Post.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    DeriveActiveEnum,
+    Deserialize,
+    Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[default]
+    #[sea_orm(string_value = "like")]
+    Like,
+    #[sea_orm(string_value = "love")]
+    Love,
+    #[sea_orm(string_value = "laugh")]
+    Laugh,
+    #[sea_orm(string_value = "wow")]
+    Wow,
+    #[sea_orm(string_value = "sad")]
+    Sad,
+    #[sea_orm(string_value = "angry")]
+    Angry,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Like => write!(f, "like"),
+            Kind::Love => write!(f, "love"),
+            Kind::Laugh => write!(f, "laugh"),
+            Kind::Wow => write!(f, "wow"),
+            Kind::Sad => write!(f, "sad"),
+            Kind::Angry => write!(f, "angry"),
+        }
+    }
+}
+
+impl std::str::FromStr for Kind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "like" => Ok(Kind::Like),
+            "love" => Ok(Kind::Love),
+            "laugh" => Ok(Kind::Laugh),
+            "wow" => Ok(Kind::Wow),
+            "sad" => Ok(Kind::Sad),
+            "angry" => Ok(Kind::Angry),
+            other => Err(format!("Reaction '{other}' not found")),
+        }
+    }
+}
+
+/// How many reactions of each kind a post has, and which of them are the caller's.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Summary {
+    pub counts: BTreeMap<Kind, u64>,
+    pub mine: Vec<Kind>,
+}
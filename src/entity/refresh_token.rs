@@ -0,0 +1,60 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "refresh_tokens")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Every token rotated from the same login shares a family, so replaying any used
+    /// token can revoke all of them at once.
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub family_id: Uuid,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            created_at: chrono::Utc::now(),
+            expires_at: chrono::Utc::now(),
+            family_id: Uuid::now_v7(),
+            revoked_at: None,
+            token_hash: String::new(),
+            used_at: None,
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
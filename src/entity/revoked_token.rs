@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// An access token that was signed out before it expired. Entries are only useful until
+/// `expires_at`, after which the token fails verification on its own.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "revoked_tokens")]
+pub struct Model {
+    /// The `jti` claim of the revoked token.
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// Stored as a BSON date so Mongo's TTL index can expire it.
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    #[sea_orm(indexed)]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,64 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// A signed-in device, from login until it signs out or stops refreshing. The id is the
+/// `sid` claim of its access tokens and the `family_id` of its refresh tokens.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(alias = "id", rename = "_id")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Pushed back whenever the session refreshes, and stored as a BSON date so Mongo's
+    /// TTL index can expire it.
+    #[serde(with = "bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    #[sea_orm(indexed)]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// The address of the last login or refresh, `None` when it can't be told.
+    pub ip: Option<String>,
+    /// The `jti` of the latest access token issued to the session.
+    #[serde_as(as = "DisplayFromStr")]
+    pub jti: Uuid,
+    pub last_seen_at: chrono::DateTime<chrono::Utc>,
+    pub user_agent: String,
+    #[serde_as(as = "DisplayFromStr")]
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            created_at: chrono::Utc::now(),
+            expires_at: chrono::Utc::now(),
+            ip: None,
+            jti: Uuid::now_v7(),
+            last_seen_at: chrono::Utc::now(),
+            user_agent: String::new(),
+            user_id: Uuid::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -2,6 +2,9 @@ use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
 
+/// Id of the author that posts of deleted users are reassigned to, see `OrphanedPosts`.
+pub const TOMBSTONE_ID: Uuid = Uuid::from_u128(1);
+
 #[serde_as]
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Deserialize, Serialize)]
 #[sea_orm(table_name = "users")]
@@ -14,18 +17,100 @@ pub struct Model {
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[sea_orm(unique)]
     pub email: String,
+    #[serde(default)]
+    pub email_verified_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Set once the user has confirmed their authenticator, from then on signing in
+    /// needs a TOTP or recovery code.
+    #[serde(default)]
+    pub mfa_enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The last TOTP time step that was accepted, so a code can't be used twice.
+    #[serde(default, skip_serializing)]
+    pub mfa_last_step: Option<i64>,
+    /// Base32 TOTP secret, set from enrolment until MFA is turned off. It is written to
+    /// the database only by `UserRepository::update_user_mfa`, never serialized.
+    #[serde(default, skip_serializing)]
+    pub mfa_secret: Option<String>,
     pub name: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub password: String,
     #[serde(default)]
     #[sea_orm(ignore)]
     pub posts: Vec<super::post::Model>,
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// What a user is allowed to do is decided by `adapter::policy` based on this.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Deserialize, Serialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[sea_orm(string_value = "admin")]
+    Admin,
+    #[default]
+    #[sea_orm(string_value = "user")]
+    User,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::User => write!(f, "user"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "admin" => Ok(Role::Admin),
+            "user" => Ok(Role::User),
+            other => Err(format!("Invalid role '{other}'")),
+        }
+    }
+}
+
+/// What happens to a user's posts and comments when the user is deleted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrphanedPosts {
+    #[default]
+    Delete,
+    /// Keeps the posts, attributed to the `Model::tombstone` author.
+    Reassign,
+}
+
+impl std::str::FromStr for OrphanedPosts {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "delete" => Ok(OrphanedPosts::Delete),
+            "reassign" => Ok(OrphanedPosts::Reassign),
+            other => Err(format!("Invalid orphaned posts mode '{other}'")),
+        }
+    }
 }
 
 impl Model {
+    /// The placeholder author of reassigned posts. Its email doesn't pass validation
+    /// and it has no password, so nobody can register as or sign in to it.
+    pub fn tombstone() -> Self {
+        Model {
+            id: TOMBSTONE_ID,
+            email: "deleted-user".to_string(),
+            name: "Deleted user".to_string(),
+            ..Default::default()
+        }
+    }
+
     pub fn set_password(mut self, value: String) -> Self {
         self.password = value;
-        return self;
+        self
     }
 }
 
@@ -35,9 +120,14 @@ impl Default for Model {
             id: Uuid::now_v7(),
             created_at: chrono::Utc::now(),
             email: String::new(),
+            email_verified_at: None,
+            mfa_enabled_at: None,
+            mfa_last_step: None,
+            mfa_secret: None,
             name: String::new(),
             password: String::new(),
             posts: Vec::new(),
+            role: Role::default(),
         }
     }
 }
@@ -12,10 +12,20 @@ use std::sync::Arc;
 pub struct AppState {
     repo: Arc<dyn repository::Repository>,
     auth: Arc<adapter::Auth>,
+    mailer: Arc<dyn adapter::Mailer>,
+    /// `None` unless OIDC login is configured.
+    oidc: Option<Arc<adapter::Oidc>>,
 }
 
 fn main() -> std::io::Result<()> {
     env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("migrate") => return controller::cli::migrate(&args[1..]),
+        Some("role") => return controller::cli::role(&args[1..]),
+        _ => {}
+    }
+
     match config::framework_type().as_str() {
         "actix" => {
             log::debug!("Using Actix as framework");
@@ -0,0 +1,767 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::entity::*;
+use crate::repository::*;
+
+#[derive(Default)]
+pub struct MemoryRepository {
+    api_keys: RwLock<BTreeMap<Uuid, api_key::Model>>,
+    comments: RwLock<BTreeMap<Uuid, comment::Model>>,
+    login_attempts: RwLock<BTreeMap<String, login_attempt::Model>>,
+    one_time_tokens: RwLock<BTreeMap<Uuid, one_time_token::Model>>,
+    posts: RwLock<BTreeMap<Uuid, post::Model>>,
+    reactions: RwLock<BTreeMap<Uuid, reaction::Model>>,
+    refresh_tokens: RwLock<BTreeMap<Uuid, refresh_token::Model>>,
+    revoked_tokens: RwLock<BTreeMap<Uuid, revoked_token::Model>>,
+    sessions: RwLock<BTreeMap<Uuid, session::Model>>,
+    users: RwLock<BTreeMap<Uuid, user::Model>>,
+}
+
+#[async_trait]
+impl Repository for MemoryRepository {
+    async fn check_health(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), AppError> {
+        self.api_keys.write().await.clear();
+        self.comments.write().await.clear();
+        self.login_attempts.write().await.clear();
+        self.one_time_tokens.write().await.clear();
+        self.posts.write().await.clear();
+        self.reactions.write().await.clear();
+        self.refresh_tokens.write().await.clear();
+        self.revoked_tokens.write().await.clear();
+        self.sessions.write().await.clear();
+        self.users.write().await.clear();
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn init(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn migrate_down(&self, _steps: Option<u32>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn migrate_up(&self, _steps: Option<u32>) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, AppError> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for MemoryRepository {
+    async fn create_api_key(&self, key: api_key::Model) -> Result<api_key::Model, AppError> {
+        let mut keys = self.api_keys.write().await;
+        if keys.values().any(|k| k.key_hash == key.key_hash) {
+            return Err(AppError::Conflict("API key already exists".to_string()));
+        }
+
+        keys.insert(key.id, key.clone());
+        Ok(key)
+    }
+
+    async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let mut keys = self.api_keys.write().await;
+        match keys.get(&id) {
+            Some(key) if key.user_id == user_id => {
+                keys.remove(&id);
+                Ok(())
+            }
+            _ => Err(AppError::NotFound(format!("API key '{id}' not found"))),
+        }
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<api_key::Model, AppError> {
+        let keys = self.api_keys.read().await;
+        match keys.values().find(|k| k.key_hash == key_hash) {
+            Some(key) => Ok(key.clone()),
+            None => Err(AppError::NotFound("API key not found".to_string())),
+        }
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<api_key::Model>, AppError> {
+        let keys = self.api_keys.read().await;
+        // Ids are UUIDv7, so the map is already in creation order.
+        let keys = keys.values().filter(|k| k.user_id == user_id).cloned();
+        Ok(keys.collect())
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MemoryRepository {
+    async fn create_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        self.comments
+            .write()
+            .await
+            .insert(comment.id, comment.clone());
+        Ok(comment)
+    }
+
+    async fn delete_comment_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        let mut comments = self.comments.write().await;
+        delete_threads(&mut comments, |comment| comment.id == id);
+        Ok(())
+    }
+
+    async fn get_comment_by_id(&self, id: Uuid) -> Result<comment::Model, AppError> {
+        let comments = self.comments.read().await;
+        let users = self.users.read().await;
+        let Some(comment) = comments.get(&id) else {
+            return Err(AppError::NotFound(format!("Comment '{id}' not found")));
+        };
+        match users.get(&comment.user_id) {
+            Some(user) => Ok(comment.clone().set_user(public_user(user))),
+            None => Err(AppError::NotFound(format!(
+                "Comment '{id}' doesn't have user"
+            ))),
+        }
+    }
+
+    async fn get_comments(&self, post_id: Uuid) -> Result<Vec<comment::Model>, AppError> {
+        let comments = self.comments.read().await;
+        let users = self.users.read().await;
+        // Ids are UUIDv7, so the map is already in creation order.
+        let result = comments
+            .values()
+            .filter(|comment| comment.post_id == post_id)
+            .filter_map(|comment| {
+                let user = users.get(&comment.user_id)?;
+                Some(comment.clone().set_user(public_user(user)))
+            })
+            .collect();
+        Ok(result)
+    }
+
+    async fn update_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        if let Some(existing) = self.comments.write().await.get_mut(&comment.id) {
+            existing.body = comment.body.clone();
+            existing.updated_at = comment.updated_at;
+        }
+        Ok(comment)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for MemoryRepository {
+    async fn delete_login_attempt(&self, id: String) -> Result<(), AppError> {
+        self.login_attempts.write().await.remove(&id);
+        Ok(())
+    }
+
+    async fn get_login_attempt(
+        &self,
+        id: String,
+    ) -> Result<Option<login_attempt::Model>, AppError> {
+        let now = chrono::Utc::now();
+        let attempts = self.login_attempts.read().await;
+        Ok(attempts.get(&id).filter(|a| a.expires_at > now).cloned())
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<login_attempt::Model, AppError> {
+        let now = chrono::Utc::now();
+        let mut attempts = self.login_attempts.write().await;
+        let attempt = attempts.entry(id.clone()).or_insert(login_attempt::Model {
+            id,
+            failures: 0,
+            last_failed_at: now,
+            expires_at: now,
+        });
+        if attempt.expires_at <= now {
+            attempt.failures = 0;
+        }
+        attempt.failures += 1;
+        attempt.last_failed_at = now;
+        attempt.expires_at = expires_at;
+        Ok(attempt.clone())
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for MemoryRepository {
+    async fn create_one_time_token(
+        &self,
+        token: one_time_token::Model,
+    ) -> Result<one_time_token::Model, AppError> {
+        let mut tokens = self.one_time_tokens.write().await;
+        if tokens.values().any(|t| t.token_hash == token.token_hash) {
+            return Err(AppError::Conflict("Token already exists".to_string()));
+        }
+
+        tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn delete_one_time_tokens(
+        &self,
+        user_id: Uuid,
+        purpose: one_time_token::Purpose,
+    ) -> Result<(), AppError> {
+        self.one_time_tokens
+            .write()
+            .await
+            .retain(|_, t| t.user_id != user_id || t.purpose != purpose);
+        Ok(())
+    }
+
+    async fn get_one_time_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<one_time_token::Model, AppError> {
+        let tokens = self.one_time_tokens.read().await;
+        match tokens.values().find(|t| t.token_hash == token_hash) {
+            Some(token) => Ok(token.clone()),
+            None => Err(AppError::NotFound("Token not found".to_string())),
+        }
+    }
+
+    async fn mark_one_time_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let mut tokens = self.one_time_tokens.write().await;
+        match tokens.get_mut(&id) {
+            Some(token) if token.used_at.is_none() => {
+                token.used_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl PostRepository for MemoryRepository {
+    async fn create_post(&self, post: post::Model) -> Result<post::Model, AppError> {
+        self.posts.write().await.insert(post.id, post.clone());
+        Ok(post)
+    }
+
+    async fn delete_post_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        let mut comments = self.comments.write().await;
+        let mut posts = self.posts.write().await;
+        let mut reactions = self.reactions.write().await;
+        comments.retain(|_, comment| comment.post_id != id);
+        posts.remove(&id);
+        reactions.retain(|_, reaction| reaction.post_id != id);
+        Ok(())
+    }
+
+    async fn get_post_by_id(&self, id: Uuid) -> Result<post::Model, AppError> {
+        let posts = self.posts.read().await;
+        let users = self.users.read().await;
+        let Some(post) = posts.get(&id) else {
+            return Err(AppError::NotFound(format!("Post '{id}' not found")));
+        };
+        match users.get(&post.user_id) {
+            Some(user) => Ok(post.clone().set_user(public_user(user))),
+            None => Err(AppError::NotFound(format!("Post '{id}' doesn't have user"))),
+        }
+    }
+
+    async fn get_posts(
+        &self,
+        filter: post::Pagination,
+    ) -> Result<(Vec<post::Model>, u64), AppError> {
+        let posts = self.posts.read().await;
+        let users = self.users.read().await;
+        let mut found: Vec<&post::Model> = posts
+            .values()
+            .rev()
+            .filter(|post| filter.filter.matches(post))
+            .collect();
+        let total = found.len() as u64;
+        if let Some(sort) = filter.sort {
+            found.sort_by(|a, b| sort.compare(a, b));
+        }
+
+        found.retain(|post| {
+            filter.after.is_none_or(|after| post.id < after.0)
+                && filter.before.is_none_or(|before| post.id > before.0)
+        });
+        let found: Vec<&post::Model> = match filter.before {
+            // The posts closest to the cursor are the last of those newer than it.
+            Some(_) => {
+                let start = found.len().saturating_sub(filter.limit as usize);
+                found.split_off(start)
+            }
+            None => found
+                .into_iter()
+                .skip(filter.offset as usize)
+                .take(filter.limit as usize)
+                .collect(),
+        };
+
+        let result = found
+            .into_iter()
+            .filter_map(|post| {
+                let user = users.get(&post.user_id)?;
+                Some(post.clone().set_user(public_user(user)))
+            })
+            .collect();
+        Ok((result, total))
+    }
+
+    async fn search_posts(
+        &self,
+        query: post::SearchQuery,
+    ) -> Result<(Vec<post::SearchResult>, u64), AppError> {
+        let posts = self.posts.read().await;
+        let users = self.users.read().await;
+        let filter = query.pagination().filter;
+        let candidates = posts
+            .values()
+            .filter(|post| filter.matches(post))
+            .filter_map(|post| {
+                let user = users.get(&post.user_id)?;
+                Some(post.clone().set_user(public_user(user)))
+            });
+        Ok(post::search(candidates, &query))
+    }
+
+    async fn update_post(&self, post: post::Model) -> Result<post::Model, AppError> {
+        if let Some(existing) = self.posts.write().await.get_mut(&post.id) {
+            existing.content = post.content.clone();
+            existing.title = post.title.clone();
+            existing.updated_at = post.updated_at;
+        }
+        Ok(post)
+    }
+}
+
+#[async_trait]
+impl ReactionRepository for MemoryRepository {
+    async fn create_reaction(&self, reaction: reaction::Model) -> Result<(), AppError> {
+        let mut reactions = self.reactions.write().await;
+        if !reactions.values().any(|r| {
+            r.post_id == reaction.post_id
+                && r.user_id == reaction.user_id
+                && r.kind == reaction.kind
+        }) {
+            reactions.insert(reaction.id, reaction);
+        }
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: reaction::Kind,
+    ) -> Result<(), AppError> {
+        self.reactions
+            .write()
+            .await
+            .retain(|_, r| !(r.post_id == post_id && r.user_id == user_id && r.kind == kind));
+        Ok(())
+    }
+
+    async fn get_reaction_summaries(
+        &self,
+        post_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, reaction::Summary>, AppError> {
+        let reactions = self.reactions.read().await;
+        let mut summaries: HashMap<Uuid, reaction::Summary> = HashMap::new();
+        for reaction in reactions.values().filter(|r| post_ids.contains(&r.post_id)) {
+            let summary = summaries.entry(reaction.post_id).or_default();
+            *summary.counts.entry(reaction.kind).or_default() += 1;
+            if reaction.user_id == user_id {
+                summary.mine.push(reaction.kind);
+            }
+        }
+        for summary in summaries.values_mut() {
+            summary.mine.sort();
+        }
+        Ok(summaries)
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for MemoryRepository {
+    async fn create_refresh_token(
+        &self,
+        token: refresh_token::Model,
+    ) -> Result<refresh_token::Model, AppError> {
+        let mut tokens = self.refresh_tokens.write().await;
+        if tokens.values().any(|t| t.token_hash == token.token_hash) {
+            return Err(AppError::Conflict(
+                "Refresh token already exists".to_string(),
+            ));
+        }
+
+        tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<refresh_token::Model, AppError> {
+        let tokens = self.refresh_tokens.read().await;
+        match tokens.values().find(|t| t.token_hash == token_hash) {
+            Some(token) => Ok(token.clone()),
+            None => Err(AppError::NotFound("Refresh token not found".to_string())),
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let mut tokens = self.refresh_tokens.write().await;
+        match tokens.get_mut(&id) {
+            Some(token) if token.used_at.is_none() => {
+                token.used_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        self.refresh_tokens
+            .write()
+            .await
+            .values_mut()
+            .filter(|t| t.family_id == family_id && t.revoked_at.is_none())
+            .for_each(|t| t.revoked_at = Some(now));
+        Ok(())
+    }
+
+    async fn revoke_user_refresh_tokens(&self, user_id: Uuid) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        self.refresh_tokens
+            .write()
+            .await
+            .values_mut()
+            .filter(|t| t.user_id == user_id && t.revoked_at.is_none())
+            .for_each(|t| t.revoked_at = Some(now));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for MemoryRepository {
+    async fn create_revoked_token(&self, token: revoked_token::Model) -> Result<(), AppError> {
+        self.revoked_tokens
+            .write()
+            .await
+            .entry(token.id)
+            .or_insert(token);
+        Ok(())
+    }
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        self.revoked_tokens
+            .write()
+            .await
+            .retain(|_, token| token.expires_at > now);
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, id: Uuid) -> Result<bool, AppError> {
+        Ok(self.revoked_tokens.read().await.contains_key(&id))
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MemoryRepository {
+    async fn create_session(&self, session: session::Model) -> Result<session::Model, AppError> {
+        self.sessions
+            .write()
+            .await
+            .insert(session.id, session.clone());
+        Ok(session)
+    }
+
+    async fn delete_expired_sessions(&self) -> Result<(), AppError> {
+        let now = chrono::Utc::now();
+        self.sessions
+            .write()
+            .await
+            .retain(|_, s| s.expires_at > now);
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let mut sessions = self.sessions.write().await;
+        match sessions.get(&id) {
+            Some(session) if session.user_id == user_id => {
+                sessions.remove(&id);
+                Ok(())
+            }
+            _ => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+        }
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<(), AppError> {
+        self.sessions
+            .write()
+            .await
+            .retain(|_, s| s.user_id != user_id);
+        Ok(())
+    }
+
+    async fn get_session_by_id(&self, id: Uuid) -> Result<session::Model, AppError> {
+        match self.sessions.read().await.get(&id) {
+            Some(session) => Ok(session.clone()),
+            None => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+        }
+    }
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<session::Model>, AppError> {
+        let now = chrono::Utc::now();
+        let sessions = self.sessions.read().await;
+        // Ids are UUIDv7, so the map is already in creation order.
+        let sessions = sessions
+            .values()
+            .filter(|s| s.user_id == user_id && s.expires_at > now)
+            .cloned();
+        Ok(sessions.collect())
+    }
+
+    async fn update_session(&self, session: session::Model) -> Result<(), AppError> {
+        let mut sessions = self.sessions.write().await;
+        match sessions.get_mut(&session.id) {
+            Some(existing) => {
+                existing.expires_at = session.expires_at;
+                existing.ip = session.ip;
+                existing.jti = session.jti;
+                existing.last_seen_at = session.last_seen_at;
+                existing.user_agent = session.user_agent;
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!(
+                "Session '{}' not found",
+                session.id
+            ))),
+        }
+    }
+
+    async fn update_session_last_seen(
+        &self,
+        id: Uuid,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError> {
+        if let Some(session) = self.sessions.write().await.get_mut(&id) {
+            session.last_seen_at = last_seen_at;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn create_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        let mut users = self.users.write().await;
+        if users.values().any(|u| u.email == user.email) {
+            return Err(AppError::Conflict("User already exists".to_string()));
+        }
+
+        users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    async fn delete_user_by_id(
+        &self,
+        id: Uuid,
+        posts: user::OrphanedPosts,
+    ) -> Result<(), AppError> {
+        // Holding every lock at once makes the deletion atomic to other callers.
+        let mut api_keys = self.api_keys.write().await;
+        let mut comments = self.comments.write().await;
+        let mut one_time_tokens = self.one_time_tokens.write().await;
+        let mut all_posts = self.posts.write().await;
+        let mut reactions = self.reactions.write().await;
+        let mut refresh_tokens = self.refresh_tokens.write().await;
+        let mut sessions = self.sessions.write().await;
+        let mut users = self.users.write().await;
+        match posts {
+            user::OrphanedPosts::Delete => {
+                delete_threads(&mut comments, |comment| {
+                    comment.user_id == id
+                        || all_posts
+                            .get(&comment.post_id)
+                            .is_some_and(|post| post.user_id == id)
+                });
+                reactions.retain(|_, reaction| {
+                    all_posts
+                        .get(&reaction.post_id)
+                        .is_none_or(|post| post.user_id != id)
+                });
+                all_posts.retain(|_, post| post.user_id != id);
+            }
+            user::OrphanedPosts::Reassign => {
+                users
+                    .entry(user::TOMBSTONE_ID)
+                    .or_insert_with(user::Model::tombstone);
+                comments
+                    .values_mut()
+                    .filter(|comment| comment.user_id == id)
+                    .for_each(|comment| comment.user_id = user::TOMBSTONE_ID);
+                all_posts
+                    .values_mut()
+                    .filter(|post| post.user_id == id)
+                    .for_each(|post| post.user_id = user::TOMBSTONE_ID);
+            }
+        }
+        api_keys.retain(|_, k| k.user_id != id);
+        one_time_tokens.retain(|_, t| t.user_id != id);
+        reactions.retain(|_, r| r.user_id != id);
+        refresh_tokens.retain(|_, t| t.user_id != id);
+        sessions.retain(|_, s| s.user_id != id);
+        users.remove(&id);
+        Ok(())
+    }
+
+    async fn get_user_by_email(&self, email: String) -> Result<user::Model, AppError> {
+        let users = self.users.read().await;
+        match users.values().find(|u| u.email == email) {
+            Some(user) => Ok(user.clone()),
+            None => Err(AppError::NotFound(format!("User '{email}' not found"))),
+        }
+    }
+
+    async fn get_user_by_id(&self, id: Uuid) -> Result<user::Model, AppError> {
+        match self.users.read().await.get(&id) {
+            Some(user) => Ok(user.clone()),
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+
+    async fn mark_user_mfa_step_used(&self, id: Uuid, step: i64) -> Result<bool, AppError> {
+        match self.users.write().await.get_mut(&id) {
+            Some(user) if user.mfa_last_step.is_none_or(|last| last < step) => {
+                user.mfa_last_step = Some(step);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn update_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        let mut users = self.users.write().await;
+        if users
+            .values()
+            .any(|u| u.email == user.email && u.id != user.id)
+        {
+            return Err(AppError::Conflict("Email is already in use".to_string()));
+        }
+
+        let id = user.id;
+        match users.get_mut(&id) {
+            Some(existing) => {
+                existing.email = user.email;
+                existing.email_verified_at = user.email_verified_at;
+                existing.name = user.name;
+                Ok(existing.clone())
+            }
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+
+    async fn update_user_mfa(
+        &self,
+        id: Uuid,
+        secret: Option<String>,
+        enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError> {
+        match self.users.write().await.get_mut(&id) {
+            Some(user) => {
+                user.mfa_enabled_at = enabled_at;
+                user.mfa_secret = secret;
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+
+    async fn update_user_password(&self, id: Uuid, password: String) -> Result<(), AppError> {
+        match self.users.write().await.get_mut(&id) {
+            Some(user) => {
+                user.password = password;
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+
+    async fn update_user_role(&self, id: Uuid, role: user::Role) -> Result<(), AppError> {
+        match self.users.write().await.get_mut(&id) {
+            Some(user) => {
+                user.role = role;
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+
+    async fn verify_user_email(&self, id: Uuid) -> Result<(), AppError> {
+        match self.users.write().await.get_mut(&id) {
+            Some(user) => {
+                user.email_verified_at.get_or_insert_with(chrono::Utc::now);
+                Ok(())
+            }
+            None => Err(AppError::NotFound(format!("User '{id}' not found"))),
+        }
+    }
+}
+
+impl MemoryRepository {
+    pub fn into_shared(self) -> Arc<dyn Repository> {
+        Arc::new(self)
+    }
+}
+
+/// Deletes the comments matching `roots` along with every reply below them.
+fn delete_threads(
+    comments: &mut BTreeMap<Uuid, comment::Model>,
+    roots: impl Fn(&comment::Model) -> bool,
+) {
+    let mut deleted: HashSet<Uuid> = comments
+        .values()
+        .filter(|comment| roots(comment))
+        .map(|comment| comment.id)
+        .collect();
+    loop {
+        let replies: Vec<Uuid> = comments
+            .values()
+            .filter(|comment| !deleted.contains(&comment.id))
+            .filter(|comment| comment.parent_id.is_some_and(|id| deleted.contains(&id)))
+            .map(|comment| comment.id)
+            .collect();
+        if replies.is_empty() {
+            break;
+        }
+        deleted.extend(replies);
+    }
+    comments.retain(|id, _| !deleted.contains(id));
+}
+
+/// Mirrors the projection the other backends apply when joining a post's author.
+fn public_user(user: &user::Model) -> user::Model {
+    user::Model {
+        id: user.id,
+        created_at: user.created_at,
+        email: user.email.clone(),
+        name: user.name.clone(),
+        role: user.role,
+        ..Default::default()
+    }
+}
@@ -1,60 +1,246 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     config,
     entity::{error::AppError, *},
-    repository::{mongo::MongoRepository, postgres::PostgresRepository},
+    repository::{mongo::MongoRepository, postgres::PostgresRepository, sqlite::SqliteRepository},
 };
 use async_trait::async_trait;
-use mongodb::{
-    Client, IndexModel,
-    options::{ClientOptions, IndexOptions},
-};
+use mongodb::{Client, options::ClientOptions};
 use sea_orm::{ConnectOptions, Database};
+use serde::Serialize;
 use uuid::Uuid;
 
+mod memory;
 mod mongo;
 mod postgres;
+mod sqlite;
+
+pub use memory::MemoryRepository;
+
+#[async_trait]
+pub trait ApiKeyRepository {
+    async fn create_api_key(&self, key: api_key::Model) -> Result<api_key::Model, AppError>;
+    /// Only deletes the key if it belongs to `user_id`, returning `AppError::NotFound`
+    /// otherwise.
+    async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError>;
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<api_key::Model, AppError>;
+    /// The user's keys, oldest first.
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<api_key::Model>, AppError>;
+}
+
+#[async_trait]
+pub trait CommentRepository {
+    async fn create_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError>;
+    /// Also deletes the replies to the comment, and their replies in turn.
+    async fn delete_comment_by_id(&self, id: Uuid) -> Result<(), AppError>;
+    async fn get_comment_by_id(&self, id: Uuid) -> Result<comment::Model, AppError>;
+    /// The post's comments and replies, oldest first.
+    async fn get_comments(&self, post_id: Uuid) -> Result<Vec<comment::Model>, AppError>;
+    async fn update_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError>;
+}
+
+#[async_trait]
+pub trait LoginAttemptRepository {
+    async fn delete_login_attempt(&self, id: String) -> Result<(), AppError>;
+    async fn get_login_attempt(&self, id: String)
+    -> Result<Option<login_attempt::Model>, AppError>;
+    /// Counts a failed login, starting over from one if the previous count had expired,
+    /// and returns the updated count.
+    async fn record_failed_login(
+        &self,
+        id: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<login_attempt::Model, AppError>;
+}
+
+#[async_trait]
+pub trait OneTimeTokenRepository {
+    async fn create_one_time_token(
+        &self,
+        token: one_time_token::Model,
+    ) -> Result<one_time_token::Model, AppError>;
+    async fn delete_one_time_tokens(
+        &self,
+        user_id: Uuid,
+        purpose: one_time_token::Purpose,
+    ) -> Result<(), AppError>;
+    async fn get_one_time_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<one_time_token::Model, AppError>;
+    /// Returns `false` when the token had already been used.
+    async fn mark_one_time_token_used(&self, id: Uuid) -> Result<bool, AppError>;
+}
 
 #[async_trait]
 pub trait PostRepository {
     async fn create_post(&self, post: post::Model) -> Result<post::Model, AppError>;
     async fn delete_post_by_id(&self, id: Uuid) -> Result<(), AppError>;
     async fn get_post_by_id(&self, id: Uuid) -> Result<post::Model, AppError>;
-    async fn get_posts(&self, query: post::Pagination) -> Result<Vec<post::Model>, AppError>;
+    /// Newest first, also when paging backwards from a `before` cursor, along with how
+    /// many posts the listing has regardless of the cursor or offset.
+    async fn get_posts(&self, query: post::Pagination)
+    -> Result<(Vec<post::Model>, u64), AppError>;
+    /// Best matches first, along with how many posts match. Queries without a single word
+    /// match nothing.
+    async fn search_posts(
+        &self,
+        query: post::SearchQuery,
+    ) -> Result<(Vec<post::SearchResult>, u64), AppError>;
     async fn update_post(&self, post: post::Model) -> Result<post::Model, AppError>;
 }
 
+#[async_trait]
+pub trait ReactionRepository {
+    /// Does nothing if the user already reacted to the post with that kind.
+    async fn create_reaction(&self, reaction: reaction::Model) -> Result<(), AppError>;
+    /// Does nothing if the user hasn't reacted to the post with that kind.
+    async fn delete_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: reaction::Kind,
+    ) -> Result<(), AppError>;
+    /// The reactions to each of the posts that has any, with `user_id`'s as `mine`.
+    async fn get_reaction_summaries(
+        &self,
+        post_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, reaction::Summary>, AppError>;
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository {
+    async fn create_refresh_token(
+        &self,
+        token: refresh_token::Model,
+    ) -> Result<refresh_token::Model, AppError>;
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<refresh_token::Model, AppError>;
+    /// Returns `false` when the token had already been used, which signals a replay.
+    async fn mark_refresh_token_used(&self, id: Uuid) -> Result<bool, AppError>;
+    async fn revoke_refresh_token_family(&self, family_id: Uuid) -> Result<(), AppError>;
+    /// Revokes every refresh token of the user, signing out all of their sessions.
+    async fn revoke_user_refresh_tokens(&self, user_id: Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+pub trait RevokedTokenRepository {
+    /// Records a revoked token, doing nothing if it was already revoked.
+    async fn create_revoked_token(&self, token: revoked_token::Model) -> Result<(), AppError>;
+    async fn delete_expired_revoked_tokens(&self) -> Result<(), AppError>;
+    async fn is_token_revoked(&self, id: Uuid) -> Result<bool, AppError>;
+}
+
+#[async_trait]
+pub trait SessionRepository {
+    async fn create_session(&self, session: session::Model) -> Result<session::Model, AppError>;
+    async fn delete_expired_sessions(&self) -> Result<(), AppError>;
+    /// Only deletes the session if it belongs to `user_id`, returning `AppError::NotFound`
+    /// otherwise.
+    async fn delete_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError>;
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<(), AppError>;
+    async fn get_session_by_id(&self, id: Uuid) -> Result<session::Model, AppError>;
+    /// The user's unexpired sessions, oldest first.
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<session::Model>, AppError>;
+    /// Saves what a refresh changes: `expires_at`, `ip`, `jti`, `last_seen_at` and
+    /// `user_agent`.
+    async fn update_session(&self, session: session::Model) -> Result<(), AppError>;
+    async fn update_session_last_seen(
+        &self,
+        id: Uuid,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError>;
+}
+
 #[async_trait]
 pub trait UserRepository {
     async fn create_user(&self, user: user::Model) -> Result<user::Model, AppError>;
-    async fn delete_user_by_id(&self, id: Uuid) -> Result<(), AppError>;
+    /// Deletes the user with their tokens, and deletes or reassigns their posts, all in
+    /// one transaction.
+    async fn delete_user_by_id(&self, id: Uuid, posts: user::OrphanedPosts)
+    -> Result<(), AppError>;
     async fn get_user_by_email(&self, email: String) -> Result<user::Model, AppError>;
     async fn get_user_by_id(&self, id: Uuid) -> Result<user::Model, AppError>;
+    /// Records `step` as the last accepted TOTP step, returning `false` if it isn't newer
+    /// than the one already recorded.
+    async fn mark_user_mfa_step_used(&self, id: Uuid, step: i64) -> Result<bool, AppError>;
+    /// Saves the profile fields (`email`, `email_verified_at` and `name`), returning
+    /// `AppError::Conflict` if the email belongs to another user.
+    async fn update_user(&self, user: user::Model) -> Result<user::Model, AppError>;
+    /// Sets the TOTP secret and when MFA was enabled. The last accepted step is kept, as
+    /// steps only move forward whichever secret is used.
+    async fn update_user_mfa(
+        &self,
+        id: Uuid,
+        secret: Option<String>,
+        enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError>;
+    async fn update_user_password(&self, id: Uuid, password: String) -> Result<(), AppError>;
+    async fn update_user_role(&self, id: Uuid, role: user::Role) -> Result<(), AppError>;
+    /// Sets `email_verified_at` to now, keeping the original time if already verified.
+    async fn verify_user_email(&self, id: Uuid) -> Result<(), AppError>;
 }
 
 #[async_trait]
-pub trait Repository: PostRepository + Send + Sync + UserRepository {
+pub trait Repository:
+    ApiKeyRepository
+    + CommentRepository
+    + LoginAttemptRepository
+    + OneTimeTokenRepository
+    + PostRepository
+    + ReactionRepository
+    + RefreshTokenRepository
+    + RevokedTokenRepository
+    + Send
+    + SessionRepository
+    + Sync
+    + UserRepository
+{
     async fn check_health(&self) -> Result<(), AppError>;
+    /// Deletes every record but keeps the schema and the applied migrations.
     async fn clear(&self) -> Result<(), AppError>;
+    /// Closes the connections to the database, the repository can't be used afterwards.
+    async fn close(&self) -> Result<(), AppError>;
     async fn init(&self) -> Result<(), AppError>;
+    async fn migrate_down(&self, steps: Option<u32>) -> Result<(), AppError>;
+    async fn migrate_up(&self, steps: Option<u32>) -> Result<(), AppError>;
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, AppError>;
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationStatus {
+    pub name: String,
+    pub applied: bool,
 }
 
 pub async fn new() -> Result<Arc<dyn Repository>, AppError> {
+    let repo = connect().await?;
+    repo.init().await?;
+    Ok(repo)
+}
+
+/// Connects to the configured database without applying pending migrations.
+pub async fn connect() -> Result<Arc<dyn Repository>, AppError> {
     match config::database_type().as_str() {
+        "memory" => Ok(MemoryRepository::default().into_shared()),
         "mongo" => {
             let options = ClientOptions::parse(config::database_url()).await?;
             let client = Client::with_options(options)?;
-            let repo = MongoRepository::new(client);
-            repo.init().await?;
-            Ok(repo)
+            Ok(MongoRepository::new(client))
         }
         "postgres" => {
             let options = ConnectOptions::new(config::database_url());
             let client = Database::connect(options).await?;
-            let repo = PostgresRepository::new(client);
-            repo.init().await?;
-            Ok(repo)
+            Ok(PostgresRepository::new(client))
+        }
+        "sqlite" => {
+            let options = SqliteRepository::connect_options(config::database_url());
+            let client = Database::connect(options).await?;
+            Ok(SqliteRepository::new(client).into_shared())
         }
         other => Err(AppError::Internal {
             err: format!("Invalid database type {other}"),
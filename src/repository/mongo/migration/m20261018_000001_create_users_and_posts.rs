@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000001_create_users_and_posts"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_USERS, users_validator()).await?;
+        let options = IndexOptions::builder().unique(true).build();
+        let index = IndexModel::builder()
+            .keys(doc! { "email": 1 })
+            .options(options)
+            .build();
+        db.collection::<Document>(config::COLLECTION_USERS)
+            .create_index(index)
+            .await?;
+
+        create_or_modify_collection(db, config::COLLECTION_POSTS, posts_validator()).await?;
+        let index = IndexModel::builder().keys(doc! { "user_id": 1 }).build();
+        db.collection::<Document>(config::COLLECTION_POSTS)
+            .create_index(index)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_POSTS)
+            .drop()
+            .await?;
+        db.collection::<Document>(config::COLLECTION_USERS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn posts_validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "content", "created_at", "title", "user_id", "updated_at"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "content": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "title": {"bsonType": "string"},
+                "user_id": {"bsonType": "string"},
+                "updated_at": {"bsonType": "string"},
+            },
+        },
+    }
+}
+
+pub(super) fn users_validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "email", "name", "password"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "email": {"bsonType": "string"},
+                "name": {"bsonType": "string"},
+                "password": {"bsonType": "string"},
+            },
+        },
+    }
+}
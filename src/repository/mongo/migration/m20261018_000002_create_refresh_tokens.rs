@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000002_create_refresh_tokens"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_REFRESH_TOKENS, validator()).await?;
+        let options = IndexOptions::builder().unique(true).build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "token_hash": 1 })
+                .options(options)
+                .build(),
+            IndexModel::builder().keys(doc! { "family_id": 1 }).build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+        ];
+        db.collection::<Document>(config::COLLECTION_REFRESH_TOKENS)
+            .create_indexes(indexes)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_REFRESH_TOKENS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "expires_at", "family_id", "token_hash", "user_id"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "expires_at": {"bsonType": "string"},
+                "family_id": {"bsonType": "string"},
+                "revoked_at": {"bsonType": ["string", "null"]},
+                "token_hash": {"bsonType": "string"},
+                "used_at": {"bsonType": ["string", "null"]},
+                "user_id": {"bsonType": "string"},
+            },
+        },
+    }
+}
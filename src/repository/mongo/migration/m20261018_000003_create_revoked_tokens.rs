@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000003_create_revoked_tokens"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_REVOKED_TOKENS, validator()).await?;
+        // Mongo purges each entry as soon as the token it revokes would have expired.
+        let options = IndexOptions::builder()
+            .expire_after(Duration::from_secs(0))
+            .build();
+        let index = IndexModel::builder()
+            .keys(doc! { "expires_at": 1 })
+            .options(options)
+            .build();
+        db.collection::<Document>(config::COLLECTION_REVOKED_TOKENS)
+            .create_index(index)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_REVOKED_TOKENS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "expires_at"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "expires_at": {"bsonType": "date"},
+            },
+        },
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000005_create_one_time_tokens"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_ONE_TIME_TOKENS, validator()).await?;
+        let options = IndexOptions::builder().unique(true).build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "token_hash": 1 })
+                .options(options)
+                .build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+        ];
+        db.collection::<Document>(config::COLLECTION_ONE_TIME_TOKENS)
+            .create_indexes(indexes)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_ONE_TIME_TOKENS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "expires_at", "purpose", "token_hash", "user_id"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "expires_at": {"bsonType": "string"},
+                "purpose": {"bsonType": "string"},
+                "token_hash": {"bsonType": "string"},
+                "used_at": {"bsonType": ["string", "null"]},
+                "user_id": {"bsonType": "string"},
+            },
+        },
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database,
+    bson::{Document, doc},
+};
+
+use crate::{
+    config,
+    entity::error::AppError,
+    repository::mongo::migration::{m20261018_000004_add_role_to_users, *},
+};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000006_add_email_verified_at_to_users"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_USERS, users_validator()).await
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        let validator = m20261018_000004_add_role_to_users::users_validator();
+        create_or_modify_collection(db, config::COLLECTION_USERS, validator).await?;
+        db.collection::<Document>(config::COLLECTION_USERS)
+            .update_many(doc! {}, doc! {"$unset": {"email_verified_at": ""}})
+            .await?;
+        Ok(())
+    }
+}
+
+pub(super) fn users_validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "email", "name", "password", "role"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "email": {"bsonType": "string"},
+                "email_verified_at": {"bsonType": ["string", "null"]},
+                "name": {"bsonType": "string"},
+                "password": {"bsonType": "string"},
+                "role": {"enum": ["admin", "user"]},
+            },
+        },
+    }
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000008_create_login_attempts"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_LOGIN_ATTEMPTS, validator()).await?;
+        // Mongo forgets the failures once they expire.
+        let options = IndexOptions::builder()
+            .expire_after(Duration::from_secs(0))
+            .build();
+        let index = IndexModel::builder()
+            .keys(doc! { "expires_at": 1 })
+            .options(options)
+            .build();
+        db.collection::<Document>(config::COLLECTION_LOGIN_ATTEMPTS)
+            .create_index(index)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_LOGIN_ATTEMPTS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "failures", "last_failed_at", "expires_at"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "failures": {"bsonType": "int"},
+                "last_failed_at": {"bsonType": "string"},
+                "expires_at": {"bsonType": "date"},
+            },
+        },
+    }
+}
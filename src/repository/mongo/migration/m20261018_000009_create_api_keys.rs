@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000009_create_api_keys"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_API_KEYS, validator()).await?;
+        let options = IndexOptions::builder().unique(true).build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "key_hash": 1 })
+                .options(options)
+                .build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+        ];
+        db.collection::<Document>(config::COLLECTION_API_KEYS)
+            .create_indexes(indexes)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_API_KEYS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "key_hash", "name", "prefix", "scope", "user_id"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "expires_at": {"bsonType": ["string", "null"]},
+                "key_hash": {"bsonType": "string"},
+                "name": {"bsonType": "string"},
+                "prefix": {"bsonType": "string"},
+                "scope": {"bsonType": "string"},
+                "user_id": {"bsonType": "string"},
+            },
+        },
+    }
+}
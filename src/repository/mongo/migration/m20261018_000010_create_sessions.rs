@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000010_create_sessions"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_SESSIONS, validator()).await?;
+        // Mongo removes sessions that stopped refreshing once they expire.
+        let options = IndexOptions::builder()
+            .expire_after(Duration::from_secs(0))
+            .build();
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(options)
+                .build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+        ];
+        db.collection::<Document>(config::COLLECTION_SESSIONS)
+            .create_indexes(indexes)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_SESSIONS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "created_at", "expires_at", "jti", "last_seen_at", "user_agent", "user_id"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "expires_at": {"bsonType": "date"},
+                "ip": {"bsonType": ["string", "null"]},
+                "jti": {"bsonType": "string"},
+                "last_seen_at": {"bsonType": "string"},
+                "user_agent": {"bsonType": "string"},
+                "user_id": {"bsonType": "string"},
+            },
+        },
+    }
+}
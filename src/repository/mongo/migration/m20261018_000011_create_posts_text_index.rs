@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+    options::IndexOptions,
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+/// A collection can only have one text index.
+const INDEX_NAME: &str = "posts_text";
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000011_create_posts_text_index"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        // Titles weigh more than content when ranking matches.
+        let options = IndexOptions::builder()
+            .name(INDEX_NAME.to_string())
+            .weights(doc! {"title": 2, "content": 1})
+            .default_language("english".to_string())
+            .build();
+        let index = IndexModel::builder()
+            .keys(doc! {"title": "text", "content": "text"})
+            .options(options)
+            .build();
+        db.collection::<Document>(config::COLLECTION_POSTS)
+            .create_index(index)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_POSTS)
+            .drop_index(INDEX_NAME)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use mongodb::{
+    Database, IndexModel,
+    bson::{Document, doc},
+};
+
+use crate::{config, entity::error::AppError, repository::mongo::migration::*};
+
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    fn name(&self) -> &'static str {
+        "m20261018_000012_create_comments"
+    }
+
+    async fn up(&self, db: &Database) -> Result<(), AppError> {
+        create_or_modify_collection(db, config::COLLECTION_COMMENTS, validator()).await?;
+        let indexes = vec![
+            IndexModel::builder().keys(doc! { "parent_id": 1 }).build(),
+            IndexModel::builder().keys(doc! { "post_id": 1 }).build(),
+            IndexModel::builder().keys(doc! { "user_id": 1 }).build(),
+        ];
+        db.collection::<Document>(config::COLLECTION_COMMENTS)
+            .create_indexes(indexes)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, db: &Database) -> Result<(), AppError> {
+        db.collection::<Document>(config::COLLECTION_COMMENTS)
+            .drop()
+            .await?;
+        Ok(())
+    }
+}
+
+fn validator() -> Document {
+    doc! {
+        "$jsonSchema": {
+            "bsonType": "object",
+            "required": ["_id", "body", "created_at", "post_id", "updated_at", "user_id"],
+            "properties": {
+                "_id": {"bsonType": "string"},
+                "body": {"bsonType": "string"},
+                "created_at": {"bsonType": "string"},
+                "parent_id": {"bsonType": ["string", "null"]},
+                "post_id": {"bsonType": "string"},
+                "updated_at": {"bsonType": "string"},
+                "user_id": {"bsonType": "string"},
+            },
+        },
+    }
+}
@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use mongodb::{
+    Database,
+    bson::{Document, doc},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config, entity::error::AppError, repository::MigrationStatus};
+
+mod m20261018_000001_create_users_and_posts;
+mod m20261018_000002_create_refresh_tokens;
+mod m20261018_000003_create_revoked_tokens;
+mod m20261018_000004_add_role_to_users;
+mod m20261018_000005_create_one_time_tokens;
+mod m20261018_000006_add_email_verified_at_to_users;
+mod m20261018_000007_add_mfa_to_users;
+mod m20261018_000008_create_login_attempts;
+mod m20261018_000009_create_api_keys;
+mod m20261018_000010_create_sessions;
+mod m20261018_000011_create_posts_text_index;
+mod m20261018_000012_create_comments;
+mod m20261018_000013_create_reactions;
+
+/// A versioned change to collections, indexes or validators, the Mongo counterpart of
+/// sea-orm's `MigrationTrait`. Applied migrations are recorded by name in
+/// `config::COLLECTION_MIGRATIONS`.
+#[async_trait]
+pub trait MigrationTrait: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn up(&self, db: &Database) -> Result<(), AppError>;
+    async fn down(&self, db: &Database) -> Result<(), AppError>;
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct MigrationRecord {
+    #[serde(rename = "_id")]
+    name: String,
+    applied_at: String,
+}
+
+fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+    vec![
+        Box::new(m20261018_000001_create_users_and_posts::Migration),
+        Box::new(m20261018_000002_create_refresh_tokens::Migration),
+        Box::new(m20261018_000003_create_revoked_tokens::Migration),
+        Box::new(m20261018_000004_add_role_to_users::Migration),
+        Box::new(m20261018_000005_create_one_time_tokens::Migration),
+        Box::new(m20261018_000006_add_email_verified_at_to_users::Migration),
+        Box::new(m20261018_000007_add_mfa_to_users::Migration),
+        Box::new(m20261018_000008_create_login_attempts::Migration),
+        Box::new(m20261018_000009_create_api_keys::Migration),
+        Box::new(m20261018_000010_create_sessions::Migration),
+        Box::new(m20261018_000011_create_posts_text_index::Migration),
+        Box::new(m20261018_000012_create_comments::Migration),
+        Box::new(m20261018_000013_create_reactions::Migration),
+    ]
+}
+
+async fn applied(db: &Database) -> Result<Vec<String>, AppError> {
+    let mut cursor = db
+        .collection::<MigrationRecord>(config::COLLECTION_MIGRATIONS)
+        .find(doc! {})
+        .sort(doc! {"_id": 1})
+        .await?;
+    let mut names = vec![];
+    while cursor.advance().await? {
+        names.push(cursor.deserialize_current()?.name);
+    }
+
+    Ok(names)
+}
+
+pub async fn down(db: &Database, steps: Option<u32>) -> Result<(), AppError> {
+    let applied = applied(db).await?;
+    let pending = migrations()
+        .into_iter()
+        .rev()
+        .filter(|m| applied.iter().any(|name| name == m.name()))
+        .take(steps.map_or(usize::MAX, |steps| steps as usize));
+    for migration in pending {
+        log::info!("Rolling back migration '{}'", migration.name());
+        migration.down(db).await?;
+        db.collection::<MigrationRecord>(config::COLLECTION_MIGRATIONS)
+            .delete_one(doc! {"_id": migration.name()})
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn status(db: &Database) -> Result<Vec<MigrationStatus>, AppError> {
+    let applied = applied(db).await?;
+    let statuses = migrations()
+        .iter()
+        .map(|m| MigrationStatus {
+            name: m.name().to_string(),
+            applied: applied.iter().any(|name| name == m.name()),
+        })
+        .collect();
+    Ok(statuses)
+}
+
+pub async fn up(db: &Database, steps: Option<u32>) -> Result<(), AppError> {
+    let applied = applied(db).await?;
+    let pending = migrations()
+        .into_iter()
+        .filter(|m| !applied.iter().any(|name| name == m.name()))
+        .take(steps.map_or(usize::MAX, |steps| steps as usize));
+    for migration in pending {
+        log::info!("Applying migration '{}'", migration.name());
+        migration.up(db).await?;
+        let record = MigrationRecord {
+            name: migration.name().to_string(),
+            applied_at: Utc::now().to_string(),
+        };
+        db.collection::<MigrationRecord>(config::COLLECTION_MIGRATIONS)
+            .insert_one(record)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Creates the collection with `validator`, or replaces the validator when the collection
+/// already exists (e.g. it was implicitly created by an insert).
+async fn create_or_modify_collection(
+    db: &Database,
+    name: &str,
+    validator: Document,
+) -> Result<(), AppError> {
+    let names = db.list_collection_names().await?;
+    if names.iter().any(|n| n == name) {
+        db.run_command(doc! {"collMod": name, "validator": validator})
+            .await?;
+    } else {
+        db.create_collection(name).validator(validator).await?;
+    }
+
+    Ok(())
+}
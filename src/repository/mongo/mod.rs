@@ -1,12 +1,18 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use mongodb::{Client, bson::doc};
+use mongodb::{
+    Client,
+    bson::{DateTime, Document, doc},
+    options::{ReturnDocument, UpdateOptions},
+};
 
 use crate::config;
 use crate::entity::*;
 use crate::repository::*;
 
+mod migration;
+
 #[derive(Clone)]
 pub struct MongoRepository {
     client: Client,
@@ -23,33 +29,292 @@ impl Repository for MongoRepository {
     }
 
     async fn clear(&self) -> Result<(), AppError> {
+        let db = self.client.database(&config::database_name());
+        let collections = [
+            config::COLLECTION_API_KEYS,
+            config::COLLECTION_COMMENTS,
+            config::COLLECTION_LOGIN_ATTEMPTS,
+            config::COLLECTION_ONE_TIME_TOKENS,
+            config::COLLECTION_POSTS,
+            config::COLLECTION_REACTIONS,
+            config::COLLECTION_REFRESH_TOKENS,
+            config::COLLECTION_REVOKED_TOKENS,
+            config::COLLECTION_SESSIONS,
+            config::COLLECTION_USERS,
+        ];
+        for name in collections {
+            db.collection::<Document>(name).delete_many(doc! {}).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), AppError> {
+        self.client.clone().shutdown().await;
+        Ok(())
+    }
+
+    async fn init(&self) -> Result<(), AppError> {
+        self.migrate_up(None).await
+    }
+
+    async fn migrate_down(&self, steps: Option<u32>) -> Result<(), AppError> {
+        migration::down(&self.client.database(&config::database_name()), steps).await
+    }
+
+    async fn migrate_up(&self, steps: Option<u32>) -> Result<(), AppError> {
+        migration::up(&self.client.database(&config::database_name()), steps).await
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, AppError> {
+        migration::status(&self.client.database(&config::database_name())).await
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for MongoRepository {
+    async fn create_api_key(&self, key: api_key::Model) -> Result<api_key::Model, AppError> {
         self.client
             .database(&config::database_name())
-            .collection::<post::Model>(config::COLLECTION_POSTS)
-            .drop()
+            .collection::<api_key::Model>(config::COLLECTION_API_KEYS)
+            .insert_one(&key)
             .await?;
+        Ok(key)
+    }
 
+    async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<api_key::Model>(config::COLLECTION_API_KEYS)
+            .delete_one(doc! {"_id": id.to_string(), "user_id": user_id.to_string()})
+            .await?;
+        match result.deleted_count {
+            0 => Err(AppError::NotFound(format!("API key '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<api_key::Model, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<api_key::Model>(config::COLLECTION_API_KEYS)
+            .find_one(doc! {"key_hash": key_hash})
+            .await?;
+        match result {
+            Some(key) => Ok(key),
+            None => Err(AppError::NotFound("API key not found".to_string())),
+        }
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<api_key::Model>, AppError> {
+        let mut cursor = self
+            .client
+            .database(&config::database_name())
+            .collection::<api_key::Model>(config::COLLECTION_API_KEYS)
+            .find(doc! {"user_id": user_id.to_string()})
+            .sort(doc! {"_id": 1})
+            .await?;
+        let mut keys = vec![];
+        while cursor.advance().await? {
+            keys.push(cursor.deserialize_current()?);
+        }
+
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl CommentRepository for MongoRepository {
+    async fn create_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
         self.client
             .database(&config::database_name())
-            .collection::<user::Model>(config::COLLECTION_USERS)
-            .drop()
+            .collection::<comment::Model>(config::COLLECTION_COMMENTS)
+            .insert_one(&comment)
             .await?;
+        Ok(comment)
+    }
+
+    async fn delete_comment_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        let collection = self
+            .client
+            .database(&config::database_name())
+            .collection::<comment::Model>(config::COLLECTION_COMMENTS);
+        let mut cursor = collection
+            .aggregate(comment_threads(doc! {"_id": id.to_string()}))
+            .with_type::<Thread>()
+            .await?;
+        let mut ids = vec![];
+        while cursor.advance().await? {
+            let thread = cursor.deserialize_current()?;
+            ids.push(thread.id);
+            ids.extend(thread.replies);
+        }
+
+        collection.delete_many(doc! {"_id": {"$in": ids}}).await?;
         Ok(())
     }
 
-    async fn init(&self) -> Result<(), AppError> {
-        let options = IndexOptions::builder().unique(true).build();
-        let index = IndexModel::builder()
-            .keys(doc! { "email": 1 })
-            .options(options.clone())
-            .build();
+    async fn get_comment_by_id(&self, id: Uuid) -> Result<comment::Model, AppError> {
+        let mut cursor = self
+            .client
+            .database(&config::database_name())
+            .collection::<comment::Model>(config::COLLECTION_COMMENTS)
+            .aggregate(comments_with_user(doc! {"_id": id.to_string()}))
+            .with_type::<comment::Model>()
+            .await?;
+        if cursor.advance().await? {
+            let comment = cursor.deserialize_current()?;
+            return Ok(comment);
+        }
+
+        Err(AppError::NotFound(format!("Comment '{id}' not found")))
+    }
+
+    async fn get_comments(&self, post_id: Uuid) -> Result<Vec<comment::Model>, AppError> {
+        let mut cursor = self
+            .client
+            .database(&config::database_name())
+            .collection::<comment::Model>(config::COLLECTION_COMMENTS)
+            .aggregate(comments_with_user(doc! {"post_id": post_id.to_string()}))
+            .with_type::<comment::Model>()
+            .await?;
+        let mut comments = vec![];
+        while cursor.advance().await? {
+            comments.push(cursor.deserialize_current()?);
+        }
+
+        Ok(comments)
+    }
+
+    async fn update_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        let update = doc! {
+            "$set": {
+                "body": &comment.body,
+                "updated_at": date_string(comment.updated_at),
+            }
+        };
         self.client
             .database(&config::database_name())
-            .collection::<user::Model>(config::COLLECTION_USERS)
-            .create_index(index)
+            .collection::<comment::Model>(config::COLLECTION_COMMENTS)
+            .update_one(doc! {"_id": comment.id.to_string()}, update)
+            .await?;
+        Ok(comment)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for MongoRepository {
+    async fn delete_login_attempt(&self, id: String) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<login_attempt::Model>(config::COLLECTION_LOGIN_ATTEMPTS)
+            .delete_one(doc! {"_id": id})
             .await?;
         Ok(())
     }
+
+    async fn get_login_attempt(
+        &self,
+        id: String,
+    ) -> Result<Option<login_attempt::Model>, AppError> {
+        let now = DateTime::from_chrono(chrono::Utc::now());
+        let attempt = self
+            .client
+            .database(&config::database_name())
+            .collection::<login_attempt::Model>(config::COLLECTION_LOGIN_ATTEMPTS)
+            .find_one(doc! {"_id": id, "expires_at": {"$gt": now}})
+            .await?;
+        Ok(attempt)
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<login_attempt::Model, AppError> {
+        let now = chrono::Utc::now();
+        // An update pipeline, so the count is read and bumped in one atomic upsert.
+        let update = vec![doc! {"$set": {
+            "failures": {"$cond": [
+                {"$gt": ["$expires_at", DateTime::from_chrono(now)]},
+                {"$add": ["$failures", 1]},
+                1,
+            ]},
+            "last_failed_at": now.to_rfc3339(),
+            "expires_at": DateTime::from_chrono(expires_at),
+        }}];
+        let attempt = self
+            .client
+            .database(&config::database_name())
+            .collection::<login_attempt::Model>(config::COLLECTION_LOGIN_ATTEMPTS)
+            .find_one_and_update(doc! {"_id": id}, update)
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?;
+        match attempt {
+            Some(attempt) => Ok(attempt),
+            None => Err(AppError::Internal {
+                err: "Upserted login attempt was not returned".to_string(),
+                path: format!("{}:{}:{}", file!(), line!(), column!()),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for MongoRepository {
+    async fn create_one_time_token(
+        &self,
+        token: one_time_token::Model,
+    ) -> Result<one_time_token::Model, AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<one_time_token::Model>(config::COLLECTION_ONE_TIME_TOKENS)
+            .insert_one(&token)
+            .await?;
+        Ok(token)
+    }
+
+    async fn delete_one_time_tokens(
+        &self,
+        user_id: Uuid,
+        purpose: one_time_token::Purpose,
+    ) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<one_time_token::Model>(config::COLLECTION_ONE_TIME_TOKENS)
+            .delete_many(doc! {"user_id": user_id.to_string(), "purpose": purpose.to_string()})
+            .await?;
+        Ok(())
+    }
+
+    async fn get_one_time_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<one_time_token::Model, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<one_time_token::Model>(config::COLLECTION_ONE_TIME_TOKENS)
+            .find_one(doc! {"token_hash": token_hash})
+            .await?;
+        match result {
+            Some(token) => Ok(token),
+            None => Err(AppError::NotFound("Token not found".to_string())),
+        }
+    }
+
+    async fn mark_one_time_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let update = doc! {"$set": {"used_at": chrono::Utc::now().to_rfc3339()}};
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<one_time_token::Model>(config::COLLECTION_ONE_TIME_TOKENS)
+            .update_one(doc! {"_id": id.to_string(), "used_at": null}, update)
+            .await?;
+        Ok(result.modified_count == 1)
+    }
 }
 
 #[async_trait]
@@ -64,11 +329,16 @@ impl PostRepository for MongoRepository {
     }
 
     async fn delete_post_by_id(&self, id: Uuid) -> Result<(), AppError> {
-        self.client
-            .database(&config::database_name())
-            .collection::<post::Model>(config::COLLECTION_POSTS)
+        let db = self.client.database(&config::database_name());
+        db.collection::<post::Model>(config::COLLECTION_POSTS)
             .delete_one(doc! {"_id": id.to_string()})
             .await?;
+        db.collection::<comment::Model>(config::COLLECTION_COMMENTS)
+            .delete_many(doc! {"post_id": id.to_string()})
+            .await?;
+        db.collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+            .delete_many(doc! {"post_id": id.to_string()})
+            .await?;
         Ok(())
     }
 
@@ -80,7 +350,7 @@ impl PostRepository for MongoRepository {
                     "let": {"user_id": "$user_id"},
                     "pipeline": [
                         {"$match": {"$expr": {"$eq": ["$_id", "$$user_id"]}}},
-                        {"$project": {"name": 1, "email": 1, "created_at": 1}},
+                        {"$project": {"name": 1, "email": 1, "created_at": 1, "role": 1}},
                     ],
                     "as": "user"
                 },
@@ -103,41 +373,98 @@ impl PostRepository for MongoRepository {
         Err(AppError::NotFound(format!("Post '{id}' not found")))
     }
 
-    async fn get_posts(&self, filter: post::Pagination) -> Result<Vec<post::Model>, AppError> {
+    async fn get_posts(
+        &self,
+        filter: post::Pagination,
+    ) -> Result<(Vec<post::Model>, u64), AppError> {
+        let listing = post_filters(&filter.filter);
+        let mut position = listing.clone();
+        // Hyphenated UUIDs compare as strings the way they do as bytes.
+        if let Some(after) = filter.after {
+            position.insert("_id", doc! {"$lt": after.0.to_string()});
+        }
+        if let Some(before) = filter.before {
+            position.insert("_id", doc! {"$gt": before.0.to_string()});
+        }
+        // Kept out of a `$facet`, whose stages can't use the indexes.
+        let mut pipeline = vec![doc! {"$match": position}];
+        match (filter.sort, filter.before) {
+            (Some(sort), _) => {
+                let order = if sort.descending { -1 } else { 1 };
+                let key = match sort.field {
+                    post::SortField::CreatedAt => padded_date("created_at"),
+                    post::SortField::Title => doc! {"$toLower": "$title"},
+                    post::SortField::UpdatedAt => padded_date("updated_at"),
+                };
+                pipeline.push(doc! {"$addFields": {"sort_key": key}});
+                pipeline.push(doc! {"$sort": {"sort_key": order, "_id": order}});
+                pipeline.push(doc! {"$unset": "sort_key"});
+            }
+            // The posts closest to a `before` cursor are the oldest of those newer than it.
+            (None, Some(_)) => pipeline.push(doc! {"$sort": {"_id": 1}}),
+            (None, None) => pipeline.push(doc! {"$sort": {"_id": -1}}),
+        }
+        pipeline.push(doc! {"$skip": filter.offset as i64});
+        pipeline.push(doc! {"$limit": filter.limit as i64});
+        pipeline.extend(post_author());
+
+        let posts = self
+            .client
+            .database(&config::database_name())
+            .collection::<post::Model>(config::COLLECTION_POSTS);
+        let mut cursor = posts.aggregate(pipeline).with_type::<post::Model>().await?;
+        let mut items = vec![];
+        while cursor.advance().await? {
+            items.push(cursor.deserialize_current()?);
+        }
+        if filter.before.is_some() {
+            items.reverse();
+        }
+
+        let total = posts.count_documents(listing).await?;
+        Ok((items, total))
+    }
+
+    async fn search_posts(
+        &self,
+        query: post::SearchQuery,
+    ) -> Result<(Vec<post::SearchResult>, u64), AppError> {
+        // `$text` matches any of the words, quoted as phrases it has to match them all.
+        let terms = query.terms();
+        if terms.is_empty() {
+            return Ok((vec![], 0));
+        }
+        let search: Vec<String> = terms.iter().map(|term| format!("\"{term}\"")).collect();
+        let mut filters = post_filters(&query.pagination().filter);
+        filters.insert("$text", doc! {"$search": search.join(" ")});
         let mut pipeline = vec![
-            doc! {"$sort": {"_id": -1}},
-            doc! {"$skip": filter.offset as i64},
-            doc! {"$limit": filter.limit as i64},
-            doc! {"$lookup": {
-                    "from": config::COLLECTION_USERS,
-                    "let": {"user_id": "$user_id"},
-                    "pipeline": [
-                        {"$match": {"$expr": {"$eq": ["$_id", "$$user_id"]}}},
-                        {"$project": {"name": 1, "email": 1, "created_at": 1}},
-                    ],
-                    "as": "user"
-                },
-            },
-            doc! {"$unwind": "$user"},
-            doc! {"$project": {"user_id": 0}},
+            doc! {"$match": filters.clone()},
+            doc! {"$addFields": {"rank": {"$meta": "textScore"}}},
+            doc! {"$sort": {"rank": -1, "_id": -1}},
+            doc! {"$skip": query.offset as i64},
+            doc! {"$limit": query.limit as i64},
         ];
-        if let Some(user_id) = filter.user_id {
-            pipeline.insert(0, doc! {"$match": {"user_id": user_id.to_string()}});
-        }
+        pipeline.extend(post_author());
 
-        let mut cursor = self
+        let posts = self
             .client
             .database(&config::database_name())
-            .collection::<post::Model>(config::COLLECTION_POSTS)
+            .collection::<post::Model>(config::COLLECTION_POSTS);
+        let mut cursor = posts
             .aggregate(pipeline)
-            .with_type::<post::Model>()
+            .with_type::<post::SearchResult>()
             .await?;
-        let mut posts = vec![];
+        let mut items = vec![];
         while cursor.advance().await? {
-            posts.push(cursor.deserialize_current()?);
+            items.push(cursor.deserialize_current()?);
         }
 
-        Ok(posts)
+        // Text indexes can't highlight, so snippets are cut out here.
+        for item in items.iter_mut() {
+            item.snippet = post::search_snippet(&item.post.content, &terms);
+        }
+        let total = posts.count_documents(filters).await?;
+        Ok((items, total))
     }
 
     async fn update_post(&self, post: post::Model) -> Result<post::Model, AppError> {
@@ -145,7 +472,7 @@ impl PostRepository for MongoRepository {
             "$set": {
                 "content": &post.content,
                 "title": &post.title,
-                "updated_at": post.updated_at.to_string(),
+                "updated_at": date_string(post.updated_at),
             }
         };
         self.client
@@ -157,6 +484,319 @@ impl PostRepository for MongoRepository {
     }
 }
 
+#[async_trait]
+impl ReactionRepository for MongoRepository {
+    async fn create_reaction(&self, reaction: reaction::Model) -> Result<(), AppError> {
+        let options = UpdateOptions::builder().upsert(true).build();
+        self.client
+            .database(&config::database_name())
+            .collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+            .update_one(
+                doc! {
+                    "kind": reaction.kind.to_string(),
+                    "post_id": reaction.post_id.to_string(),
+                    "user_id": reaction.user_id.to_string(),
+                },
+                doc! {"$setOnInsert": {
+                    "_id": reaction.id.to_string(),
+                    "created_at": date_string(reaction.created_at),
+                }},
+            )
+            .with_options(options)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: reaction::Kind,
+    ) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+            .delete_one(doc! {
+                "kind": kind.to_string(),
+                "post_id": post_id.to_string(),
+                "user_id": user_id.to_string(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn get_reaction_summaries(
+        &self,
+        post_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, reaction::Summary>, AppError> {
+        #[serde_with::serde_as]
+        #[derive(serde::Deserialize)]
+        struct Key {
+            kind: reaction::Kind,
+            #[serde_as(as = "serde_with::DisplayFromStr")]
+            post_id: Uuid,
+        }
+        #[derive(serde::Deserialize)]
+        struct Group {
+            #[serde(rename = "_id")]
+            key: Key,
+            count: u64,
+            mine: bool,
+        }
+
+        let post_ids: Vec<String> = post_ids.iter().map(Uuid::to_string).collect();
+        let pipeline = vec![
+            doc! {"$match": {"post_id": {"$in": post_ids}}},
+            doc! {"$group": {
+                    "_id": {"post_id": "$post_id", "kind": "$kind"},
+                    "count": {"$sum": 1},
+                    "mine": {"$max": {"$eq": ["$user_id", user_id.to_string()]}},
+                },
+            },
+        ];
+        let mut cursor = self
+            .client
+            .database(&config::database_name())
+            .collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+            .aggregate(pipeline)
+            .with_type::<Group>()
+            .await?;
+        let mut summaries: HashMap<Uuid, reaction::Summary> = HashMap::new();
+        while cursor.advance().await? {
+            let group = cursor.deserialize_current()?;
+            let summary = summaries.entry(group.key.post_id).or_default();
+            summary.counts.insert(group.key.kind, group.count);
+            if group.mine {
+                summary.mine.push(group.key.kind);
+            }
+        }
+        for summary in summaries.values_mut() {
+            summary.mine.sort();
+        }
+
+        Ok(summaries)
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for MongoRepository {
+    async fn create_refresh_token(
+        &self,
+        token: refresh_token::Model,
+    ) -> Result<refresh_token::Model, AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .insert_one(&token)
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<refresh_token::Model, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .find_one(doc! {"token_hash": token_hash})
+            .await?;
+        match result {
+            Some(token) => Ok(token),
+            None => Err(AppError::NotFound("Refresh token not found".to_string())),
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let update = doc! {"$set": {"used_at": chrono::Utc::now().to_rfc3339()}};
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .update_one(doc! {"_id": id.to_string(), "used_at": null}, update)
+            .await?;
+        Ok(result.modified_count == 1)
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        let update = doc! {"$set": {"revoked_at": chrono::Utc::now().to_rfc3339()}};
+        self.client
+            .database(&config::database_name())
+            .collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .update_many(
+                doc! {"family_id": family_id.to_string(), "revoked_at": null},
+                update,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke_user_refresh_tokens(&self, user_id: Uuid) -> Result<(), AppError> {
+        let update = doc! {"$set": {"revoked_at": chrono::Utc::now().to_rfc3339()}};
+        self.client
+            .database(&config::database_name())
+            .collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .update_many(
+                doc! {"user_id": user_id.to_string(), "revoked_at": null},
+                update,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for MongoRepository {
+    async fn create_revoked_token(&self, token: revoked_token::Model) -> Result<(), AppError> {
+        let expires_at = DateTime::from_chrono(token.expires_at);
+        let options = UpdateOptions::builder().upsert(true).build();
+        self.client
+            .database(&config::database_name())
+            .collection::<revoked_token::Model>(config::COLLECTION_REVOKED_TOKENS)
+            .update_one(
+                doc! {"_id": token.id.to_string()},
+                doc! {"$setOnInsert": {"expires_at": expires_at}},
+            )
+            .with_options(options)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<revoked_token::Model>(config::COLLECTION_REVOKED_TOKENS)
+            .delete_many(doc! {"expires_at": {"$lte": DateTime::now()}})
+            .await?;
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, id: Uuid) -> Result<bool, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<revoked_token::Model>(config::COLLECTION_REVOKED_TOKENS)
+            .find_one(doc! {"_id": id.to_string()})
+            .await?;
+        Ok(result.is_some())
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MongoRepository {
+    async fn create_session(&self, session: session::Model) -> Result<session::Model, AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .insert_one(&session)
+            .await?;
+        Ok(session)
+    }
+
+    async fn delete_expired_sessions(&self) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .delete_many(doc! {"expires_at": {"$lte": DateTime::now()}})
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .delete_one(doc! {"_id": id.to_string(), "user_id": user_id.to_string()})
+            .await?;
+        match result.deleted_count {
+            0 => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .delete_many(doc! {"user_id": user_id.to_string()})
+            .await?;
+        Ok(())
+    }
+
+    async fn get_session_by_id(&self, id: Uuid) -> Result<session::Model, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .find_one(doc! {"_id": id.to_string()})
+            .await?;
+        match result {
+            Some(session) => Ok(session),
+            None => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+        }
+    }
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<session::Model>, AppError> {
+        let mut cursor = self
+            .client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .find(doc! {"user_id": user_id.to_string(), "expires_at": {"$gt": DateTime::now()}})
+            .sort(doc! {"_id": 1})
+            .await?;
+        let mut sessions = vec![];
+        while cursor.advance().await? {
+            sessions.push(cursor.deserialize_current()?);
+        }
+
+        Ok(sessions)
+    }
+
+    async fn update_session(&self, session: session::Model) -> Result<(), AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .update_one(
+                doc! {"_id": session.id.to_string()},
+                doc! {"$set": {
+                    "expires_at": DateTime::from_chrono(session.expires_at),
+                    "ip": session.ip,
+                    "jti": session.jti.to_string(),
+                    "last_seen_at": session.last_seen_at.to_rfc3339(),
+                    "user_agent": session.user_agent,
+                }},
+            )
+            .await?;
+        match result.matched_count {
+            0 => Err(AppError::NotFound(format!(
+                "Session '{}' not found",
+                session.id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_session_last_seen(
+        &self,
+        id: Uuid,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError> {
+        self.client
+            .database(&config::database_name())
+            .collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .update_one(
+                doc! {"_id": id.to_string()},
+                doc! {"$set": {"last_seen_at": last_seen_at.to_rfc3339()}},
+            )
+            .await?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl UserRepository for MongoRepository {
     async fn create_user(&self, user: user::Model) -> Result<user::Model, AppError> {
@@ -168,12 +808,110 @@ impl UserRepository for MongoRepository {
         Ok(user)
     }
 
-    async fn delete_user_by_id(&self, id: Uuid) -> Result<(), AppError> {
-        self.client
-            .database(&config::database_name())
-            .collection::<user::Model>(config::COLLECTION_USERS)
+    /// Transactions need Mongo to run as a replica set, a single member is enough.
+    async fn delete_user_by_id(
+        &self,
+        id: Uuid,
+        posts: user::OrphanedPosts,
+    ) -> Result<(), AppError> {
+        let db = self.client.database(&config::database_name());
+        let filter = doc! {"user_id": id.to_string()};
+        let mut session = self.client.start_session().await?;
+        session.start_transaction().await?;
+        match posts {
+            user::OrphanedPosts::Delete => {
+                let comments = db.collection::<comment::Model>(config::COLLECTION_COMMENTS);
+                let mut cursor = comments
+                    .aggregate(comment_threads(filter.clone()))
+                    .with_type::<Thread>()
+                    .session(&mut session)
+                    .await?;
+                let mut ids = vec![];
+                while cursor.advance(&mut session).await? {
+                    let thread = cursor.deserialize_current()?;
+                    ids.push(thread.id);
+                    ids.extend(thread.replies);
+                }
+                let post_ids = db
+                    .collection::<post::Model>(config::COLLECTION_POSTS)
+                    .distinct("_id", filter.clone())
+                    .session(&mut session)
+                    .await?;
+                comments
+                    .delete_many(doc! {"$or": [
+                        {"_id": {"$in": ids}},
+                        {"post_id": {"$in": &post_ids}},
+                    ]})
+                    .session(&mut session)
+                    .await?;
+                db.collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+                    .delete_many(doc! {"post_id": {"$in": post_ids}})
+                    .session(&mut session)
+                    .await?;
+                db.collection::<post::Model>(config::COLLECTION_POSTS)
+                    .delete_many(filter.clone())
+                    .session(&mut session)
+                    .await?;
+            }
+            user::OrphanedPosts::Reassign => {
+                let tombstone = user::Model::tombstone();
+                let options = UpdateOptions::builder().upsert(true).build();
+                db.collection::<user::Model>(config::COLLECTION_USERS)
+                    .update_one(
+                        doc! {"_id": tombstone.id.to_string()},
+                        doc! {"$setOnInsert": {
+                            "created_at": tombstone.created_at.to_rfc3339(),
+                            "email": &tombstone.email,
+                            "email_verified_at": null,
+                            "name": &tombstone.name,
+                            "password": &tombstone.password,
+                            "role": tombstone.role.to_string(),
+                        }},
+                    )
+                    .with_options(options)
+                    .session(&mut session)
+                    .await?;
+                db.collection::<comment::Model>(config::COLLECTION_COMMENTS)
+                    .update_many(
+                        filter.clone(),
+                        doc! {"$set": {"user_id": tombstone.id.to_string()}},
+                    )
+                    .session(&mut session)
+                    .await?;
+                db.collection::<post::Model>(config::COLLECTION_POSTS)
+                    .update_many(
+                        filter.clone(),
+                        doc! {"$set": {"user_id": tombstone.id.to_string()}},
+                    )
+                    .session(&mut session)
+                    .await?;
+            }
+        }
+        db.collection::<api_key::Model>(config::COLLECTION_API_KEYS)
+            .delete_many(filter.clone())
+            .session(&mut session)
+            .await?;
+        db.collection::<one_time_token::Model>(config::COLLECTION_ONE_TIME_TOKENS)
+            .delete_many(filter.clone())
+            .session(&mut session)
+            .await?;
+        db.collection::<reaction::Model>(config::COLLECTION_REACTIONS)
+            .delete_many(filter.clone())
+            .session(&mut session)
+            .await?;
+        db.collection::<refresh_token::Model>(config::COLLECTION_REFRESH_TOKENS)
+            .delete_many(filter.clone())
+            .session(&mut session)
+            .await?;
+        db.collection::<session::Model>(config::COLLECTION_SESSIONS)
+            .delete_many(filter)
+            .session(&mut session)
+            .await?;
+        db.collection::<user::Model>(config::COLLECTION_USERS)
             .delete_one(doc! {"_id": id.to_string()})
+            .session(&mut session)
             .await?;
+        session.commit_transaction().await?;
         Ok(())
     }
 
@@ -202,6 +940,112 @@ impl UserRepository for MongoRepository {
             None => Err(AppError::NotFound(format!("User '{id}' not found"))),
         }
     }
+
+    async fn mark_user_mfa_step_used(&self, id: Uuid, step: i64) -> Result<bool, AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(
+                doc! {
+                    "_id": id.to_string(),
+                    "$or": [{"mfa_last_step": null}, {"mfa_last_step": {"$lt": step}}],
+                },
+                doc! {"$set": {"mfa_last_step": step}},
+            )
+            .await?;
+        Ok(result.modified_count > 0)
+    }
+
+    async fn update_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        let id = user.id;
+        let update = doc! {"$set": {
+            "email": &user.email,
+            "email_verified_at": user.email_verified_at.map(|at| at.to_rfc3339()),
+            "name": &user.name,
+        }};
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(doc! {"_id": id.to_string()}, update)
+            .await
+            .map_err(|err| match AppError::from(err) {
+                AppError::Conflict(_) => AppError::Conflict("Email is already in use".to_string()),
+                err => err,
+            })?;
+        match result.matched_count {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => self.get_user_by_id(id).await,
+        }
+    }
+
+    async fn update_user_mfa(
+        &self,
+        id: Uuid,
+        secret: Option<String>,
+        enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError> {
+        let update = doc! {"$set": {
+            "mfa_enabled_at": enabled_at.map(|at| at.to_rfc3339()),
+            "mfa_secret": secret,
+        }};
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(doc! {"_id": id.to_string()}, update)
+            .await?;
+        match result.matched_count {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_user_password(&self, id: Uuid, password: String) -> Result<(), AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(
+                doc! {"_id": id.to_string()},
+                doc! {"$set": {"password": password}},
+            )
+            .await?;
+        match result.matched_count {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_user_role(&self, id: Uuid, role: user::Role) -> Result<(), AppError> {
+        let result = self
+            .client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(
+                doc! {"_id": id.to_string()},
+                doc! {"$set": {"role": role.to_string()}},
+            )
+            .await?;
+        match result.matched_count {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn verify_user_email(&self, id: Uuid) -> Result<(), AppError> {
+        self.get_user_by_id(id).await?;
+        self.client
+            .database(&config::database_name())
+            .collection::<user::Model>(config::COLLECTION_USERS)
+            .update_one(
+                doc! {"_id": id.to_string(), "email_verified_at": null},
+                doc! {"$set": {"email_verified_at": chrono::Utc::now().to_rfc3339()}},
+            )
+            .await?;
+        Ok(())
+    }
 }
 
 impl MongoRepository {
@@ -209,3 +1053,133 @@ impl MongoRepository {
         Arc::new(MongoRepository { client })
     }
 }
+
+/// A comment's id and the ids of every reply below it, as `comment_threads` finds them.
+#[derive(serde::Deserialize)]
+struct Thread {
+    #[serde(rename = "_id")]
+    id: String,
+    replies: Vec<String>,
+}
+
+/// Stages finding the comments matching `filter` along with their replies, at any depth.
+fn comment_threads(filter: Document) -> Vec<Document> {
+    vec![
+        doc! {"$match": filter},
+        doc! {"$graphLookup": {
+                "from": config::COLLECTION_COMMENTS,
+                "startWith": "$_id",
+                "connectFromField": "_id",
+                "connectToField": "parent_id",
+                "as": "replies"
+            },
+        },
+        doc! {"$project": {"replies": "$replies._id"}},
+    ]
+}
+
+/// Stages loading the comments matching `filter` with their author, oldest first.
+fn comments_with_user(filter: Document) -> Vec<Document> {
+    vec![
+        doc! {"$match": filter},
+        doc! {"$sort": {"_id": 1}},
+        doc! {"$lookup": {
+                "from": config::COLLECTION_USERS,
+                "let": {"user_id": "$user_id"},
+                "pipeline": [
+                    {"$match": {"$expr": {"$eq": ["$_id", "$$user_id"]}}},
+                    {"$project": {"name": 1, "email": 1, "created_at": 1, "role": 1}},
+                ],
+                "as": "user"
+            },
+        },
+        doc! {"$unwind": "$user"},
+        doc! {"$project": {"user_id": 0}},
+    ]
+}
+
+/// Stages replacing a post's `user_id` with its author.
+fn post_author() -> Vec<Document> {
+    vec![
+        doc! {"$lookup": {
+                "from": config::COLLECTION_USERS,
+                "let": {"user_id": "$user_id"},
+                "pipeline": [
+                    {"$match": {"$expr": {"$eq": ["$_id", "$$user_id"]}}},
+                    {"$project": {"name": 1, "email": 1, "created_at": 1, "role": 1}},
+                ],
+                "as": "user"
+            },
+        },
+        doc! {"$unwind": "$user"},
+        doc! {"$project": {"user_id": 0}},
+    ]
+}
+
+/// Written the way serde writes post dates.
+fn date_string(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+/// `at` with every fraction digit, to compare with `padded_date`.
+fn padded_date_string(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+}
+
+/// An expression padding the fraction of a date `field` to nine digits. Stored dates have
+/// as few digits as possible, and `12:00:00Z` would sort after `12:00:00.5Z` as is.
+fn padded_date(field: &str) -> Document {
+    let path = format!("${field}");
+    doc! {"$let": {
+        "vars": {
+            // Between the `.` after the seconds and the trailing `Z`, if there is one.
+            "fraction": {"$substrCP": [
+                &path,
+                20,
+                {"$max": [0, {"$subtract": [{"$strLenCP": &path}, 21]}]},
+            ]},
+        },
+        "in": {"$concat": [
+            {"$substrCP": [&path, 0, 19]},
+            ".",
+            "$$fraction",
+            {"$substrCP": ["000000000", 0, {"$subtract": [9, {"$strLenCP": "$$fraction"}]}]},
+            "Z",
+        ]},
+    }}
+}
+
+/// `post::Filter` as the filter of a `$match` stage.
+fn post_filters(filter: &post::Filter) -> Document {
+    let mut filters = doc! {};
+    let ranges = [
+        ("created_at", filter.created_after, filter.created_before),
+        ("updated_at", filter.updated_after, filter.updated_before),
+    ];
+    let mut bounds = vec![];
+    for (field, after, before) in ranges {
+        if let Some(at) = after {
+            bounds.push(doc! {"$gt": [padded_date(field), padded_date_string(at)]});
+        }
+        if let Some(at) = before {
+            bounds.push(doc! {"$lt": [padded_date(field), padded_date_string(at)]});
+        }
+    }
+    if !bounds.is_empty() {
+        filters.insert("$expr", doc! {"$and": bounds});
+    }
+    if let Some(prefix) = &filter.title_prefix {
+        let mut pattern = "^".to_string();
+        for c in prefix.chars() {
+            if "\\^$.|?*+()[]{}".contains(c) {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        filters.insert("title", doc! {"$regex": pattern, "$options": "i"});
+    }
+    if let Some(user_id) = filter.user_id {
+        filters.insert("user_id", user_id.to_string());
+    }
+    filters
+}
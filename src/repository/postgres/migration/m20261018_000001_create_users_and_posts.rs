@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Tables used to be created straight from the entities with `if_not_exists`, so this
+// migration must also adopt databases that already have them.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(Users::Id))
+                    .col(timestamp_with_time_zone(Users::CreatedAt))
+                    .col(string_uniq(Users::Email))
+                    .col(string(Users::Name))
+                    .col(string(Users::Password))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Posts::Table)
+                    .if_not_exists()
+                    .col(pk_uuid(Posts::Id))
+                    .col(string(Posts::Content))
+                    .col(timestamp_with_time_zone(Posts::CreatedAt))
+                    .col(string(Posts::Title))
+                    .col(uuid(Posts::UserId))
+                    .col(timestamp_with_time_zone(Posts::UpdatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-posts-user_id")
+                            .from(Posts::Table, Posts::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-posts-user_id")
+                    .table(Posts::Table)
+                    .col(Posts::UserId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Posts::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Users::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+    Content,
+    CreatedAt,
+    Title,
+    UserId,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+    CreatedAt,
+    Email,
+    Name,
+    Password,
+}
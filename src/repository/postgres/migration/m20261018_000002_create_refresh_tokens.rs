@@ -0,0 +1,77 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshTokens::Table)
+                    .col(pk_uuid(RefreshTokens::Id))
+                    .col(timestamp_with_time_zone(RefreshTokens::CreatedAt))
+                    .col(timestamp_with_time_zone(RefreshTokens::ExpiresAt))
+                    .col(uuid(RefreshTokens::FamilyId))
+                    .col(timestamp_with_time_zone_null(RefreshTokens::RevokedAt))
+                    .col(string_uniq(RefreshTokens::TokenHash))
+                    .col(timestamp_with_time_zone_null(RefreshTokens::UsedAt))
+                    .col(uuid(RefreshTokens::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-refresh_tokens-user_id")
+                            .from(RefreshTokens::Table, RefreshTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-refresh_tokens-family_id")
+                    .table(RefreshTokens::Table)
+                    .col(RefreshTokens::FamilyId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-refresh_tokens-user_id")
+                    .table(RefreshTokens::Table)
+                    .col(RefreshTokens::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RefreshTokens {
+    Table,
+    Id,
+    CreatedAt,
+    ExpiresAt,
+    FamilyId,
+    RevokedAt,
+    TokenHash,
+    UsedAt,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
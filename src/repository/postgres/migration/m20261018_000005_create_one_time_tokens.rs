@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OneTimeTokens::Table)
+                    .col(pk_uuid(OneTimeTokens::Id))
+                    .col(timestamp_with_time_zone(OneTimeTokens::CreatedAt))
+                    .col(timestamp_with_time_zone(OneTimeTokens::ExpiresAt))
+                    .col(string_len(OneTimeTokens::Purpose, 32))
+                    .col(string_uniq(OneTimeTokens::TokenHash))
+                    .col(timestamp_with_time_zone_null(OneTimeTokens::UsedAt))
+                    .col(uuid(OneTimeTokens::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-one_time_tokens-user_id")
+                            .from(OneTimeTokens::Table, OneTimeTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-one_time_tokens-user_id")
+                    .table(OneTimeTokens::Table)
+                    .col(OneTimeTokens::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OneTimeTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OneTimeTokens {
+    Table,
+    Id,
+    CreatedAt,
+    ExpiresAt,
+    Purpose,
+    TokenHash,
+    UsedAt,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiKeys::Table)
+                    .col(pk_uuid(ApiKeys::Id))
+                    .col(timestamp_with_time_zone(ApiKeys::CreatedAt))
+                    .col(timestamp_with_time_zone_null(ApiKeys::ExpiresAt))
+                    .col(string_uniq(ApiKeys::KeyHash))
+                    .col(string(ApiKeys::Name))
+                    .col(string(ApiKeys::Prefix))
+                    .col(string_len(ApiKeys::Scope, 16))
+                    .col(uuid(ApiKeys::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-api_keys-user_id")
+                            .from(ApiKeys::Table, ApiKeys::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-api_keys-user_id")
+                    .table(ApiKeys::Table)
+                    .col(ApiKeys::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiKeys {
+    Table,
+    Id,
+    CreatedAt,
+    ExpiresAt,
+    KeyHash,
+    Name,
+    Prefix,
+    Scope,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
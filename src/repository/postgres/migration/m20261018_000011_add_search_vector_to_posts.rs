@@ -0,0 +1,58 @@
+use sea_orm_migration::{prelude::*, sea_orm::DatabaseBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Titles weigh more than content when ranking matches.
+const SEARCH_VECTOR: &str = "setweight(to_tsvector('english', title), 'A') || \
+    setweight(to_tsvector('english', content), 'B')";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite has no `tsvector`, its searches scan the table instead.
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                "ALTER TABLE posts ADD COLUMN search_vector tsvector \
+                 GENERATED ALWAYS AS ({SEARCH_VECTOR}) STORED"
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-posts-search_vector")
+                    .table(Posts::Table)
+                    .col(Posts::SearchVector)
+                    .full_text()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Posts::Table)
+                    .drop_column(Posts::SearchVector)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    SearchVector,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Comments::Table)
+                    .col(pk_uuid(Comments::Id))
+                    .col(string(Comments::Body))
+                    .col(timestamp_with_time_zone(Comments::CreatedAt))
+                    .col(uuid_null(Comments::ParentId))
+                    .col(uuid(Comments::PostId))
+                    .col(timestamp_with_time_zone(Comments::UpdatedAt))
+                    .col(uuid(Comments::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comments-parent_id")
+                            .from(Comments::Table, Comments::ParentId)
+                            .to(Comments::Table, Comments::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comments-post_id")
+                            .from(Comments::Table, Comments::PostId)
+                            .to(Posts::Table, Posts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    // Like posts, comments are deleted or reassigned before their user.
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-comments-user_id")
+                            .from(Comments::Table, Comments::UserId)
+                            .to(Users::Table, Users::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-comments-parent_id")
+                    .table(Comments::Table)
+                    .col(Comments::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-comments-post_id")
+                    .table(Comments::Table)
+                    .col(Comments::PostId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-comments-user_id")
+                    .table(Comments::Table)
+                    .col(Comments::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Comments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Comments {
+    Table,
+    Id,
+    Body,
+    CreatedAt,
+    ParentId,
+    PostId,
+    UpdatedAt,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Reactions::Table)
+                    .col(pk_uuid(Reactions::Id))
+                    .col(timestamp_with_time_zone(Reactions::CreatedAt))
+                    .col(string_len(Reactions::Kind, 16))
+                    .col(uuid(Reactions::PostId))
+                    .col(uuid(Reactions::UserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-reactions-post_id")
+                            .from(Reactions::Table, Reactions::PostId)
+                            .to(Posts::Table, Posts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-reactions-user_id")
+                            .from(Reactions::Table, Reactions::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Also serves looking up a post's reactions.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reactions-post_id-user_id-kind")
+                    .table(Reactions::Table)
+                    .col(Reactions::PostId)
+                    .col(Reactions::UserId)
+                    .col(Reactions::Kind)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reactions-user_id")
+                    .table(Reactions::Table)
+                    .col(Reactions::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Reactions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reactions {
+    Table,
+    Id,
+    CreatedAt,
+    Kind,
+    PostId,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Posts {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
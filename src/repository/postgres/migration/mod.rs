@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+mod m20261018_000001_create_users_and_posts;
+mod m20261018_000002_create_refresh_tokens;
+mod m20261018_000003_create_revoked_tokens;
+mod m20261018_000004_add_role_to_users;
+mod m20261018_000005_create_one_time_tokens;
+mod m20261018_000006_add_email_verified_at_to_users;
+mod m20261018_000007_add_mfa_to_users;
+mod m20261018_000008_create_login_attempts;
+mod m20261018_000009_create_api_keys;
+mod m20261018_000010_create_sessions;
+mod m20261018_000011_add_search_vector_to_posts;
+mod m20261018_000012_create_comments;
+mod m20261018_000013_create_reactions;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20261018_000001_create_users_and_posts::Migration),
+            Box::new(m20261018_000002_create_refresh_tokens::Migration),
+            Box::new(m20261018_000003_create_revoked_tokens::Migration),
+            Box::new(m20261018_000004_add_role_to_users::Migration),
+            Box::new(m20261018_000005_create_one_time_tokens::Migration),
+            Box::new(m20261018_000006_add_email_verified_at_to_users::Migration),
+            Box::new(m20261018_000007_add_mfa_to_users::Migration),
+            Box::new(m20261018_000008_create_login_attempts::Migration),
+            Box::new(m20261018_000009_create_api_keys::Migration),
+            Box::new(m20261018_000010_create_sessions::Migration),
+            Box::new(m20261018_000011_add_search_vector_to_posts::Migration),
+            Box::new(m20261018_000012_create_comments::Migration),
+            Box::new(m20261018_000013_create_reactions::Migration),
+        ]
+    }
+}
@@ -1,14 +1,24 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
-use sea_orm::{IntoActiveModel, QueryOrder, QuerySelect, Schema, prelude::*, sea_query::Table};
+use sea_orm::{
+    Condition, FromQueryResult, IntoActiveModel, Order, QueryOrder, QuerySelect, Statement,
+    TransactionTrait,
+    prelude::*,
+    sea_query::{Func, LikeExpr, OnConflict, SimpleExpr},
+};
+use sea_orm_migration::MigratorTrait;
 
 use crate::entity::*;
 use crate::repository::*;
 
+mod migration;
+
+use migration::Migrator;
+
 #[derive(Clone)]
 pub struct PostgresRepository {
-    client: DatabaseConnection,
+    pub(super) client: DatabaseConnection,
 }
 
 #[async_trait]
@@ -19,32 +29,258 @@ impl Repository for PostgresRepository {
     }
 
     async fn clear(&self) -> Result<(), AppError> {
-        let builder = self.client.get_database_backend();
-        let mut schema = Table::drop();
-        self.client
-            .execute(builder.build(schema.table(post::Entity).if_exists()))
+        // Rows are deleted before those they reference, as SQLite has no `TRUNCATE`.
+        reaction::Entity::delete_many().exec(&self.client).await?;
+        comment::Entity::delete_many().exec(&self.client).await?;
+        post::Entity::delete_many().exec(&self.client).await?;
+        api_key::Entity::delete_many().exec(&self.client).await?;
+        login_attempt::Entity::delete_many()
+            .exec(&self.client)
             .await?;
-
-        schema = Table::drop();
-        self.client
-            .execute(builder.build(schema.table(user::Entity).if_exists()))
+        one_time_token::Entity::delete_many()
+            .exec(&self.client)
+            .await?;
+        refresh_token::Entity::delete_many()
+            .exec(&self.client)
             .await?;
+        revoked_token::Entity::delete_many()
+            .exec(&self.client)
+            .await?;
+        session::Entity::delete_many().exec(&self.client).await?;
+        user::Entity::delete_many().exec(&self.client).await?;
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), AppError> {
+        self.client.close_by_ref().await?;
         Ok(())
     }
 
     async fn init(&self) -> Result<(), AppError> {
-        let builder = self.client.get_database_backend();
-        let mut schema = Schema::new(builder).create_table_from_entity(user::Entity);
-        self.client
-            .execute(builder.build(schema.if_not_exists()))
+        self.migrate_up(None).await
+    }
+
+    async fn migrate_down(&self, steps: Option<u32>) -> Result<(), AppError> {
+        Migrator::down(&self.client, steps).await?;
+        Ok(())
+    }
+
+    async fn migrate_up(&self, steps: Option<u32>) -> Result<(), AppError> {
+        Migrator::up(&self.client, steps).await?;
+        Ok(())
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, AppError> {
+        let migrations = Migrator::get_migration_with_status(&self.client).await?;
+        let statuses = migrations
+            .iter()
+            .map(|migration| MigrationStatus {
+                name: migration.name().to_string(),
+                applied: migration.status() == sea_orm_migration::MigrationStatus::Applied,
+            })
+            .collect();
+        Ok(statuses)
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PostgresRepository {
+    async fn create_api_key(&self, key: api_key::Model) -> Result<api_key::Model, AppError> {
+        let key = key.into_active_model().insert(&self.client).await?;
+        Ok(key)
+    }
+
+    async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = api_key::Entity::delete_many()
+            .filter(api_key::Column::Id.eq(id))
+            .filter(api_key::Column::UserId.eq(user_id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("API key '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<api_key::Model, AppError> {
+        let result = api_key::Entity::find()
+            .filter(api_key::Column::KeyHash.eq(key_hash))
+            .one(&self.client)
+            .await?;
+        match result {
+            Some(key) => Ok(key),
+            None => Err(AppError::NotFound("API key not found".to_string())),
+        }
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<api_key::Model>, AppError> {
+        let keys = api_key::Entity::find()
+            .filter(api_key::Column::UserId.eq(user_id))
+            .order_by_asc(api_key::Column::Id)
+            .all(&self.client)
+            .await?;
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl CommentRepository for PostgresRepository {
+    async fn create_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        let comment = comment.into_active_model().insert(&self.client).await?;
+        Ok(comment)
+    }
+
+    /// Replies go with their parent through the `parent_id` foreign key.
+    async fn delete_comment_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        comment::Entity::delete_by_id(id).exec(&self.client).await?;
+        Ok(())
+    }
+
+    async fn get_comment_by_id(&self, id: Uuid) -> Result<comment::Model, AppError> {
+        let result = comment::Entity::find_by_id(id)
+            .find_also_related(user::Entity)
+            .one(&self.client)
+            .await?;
+        match result {
+            Some((comment, Some(user))) => Ok(comment.set_user(user.set_password("".to_string()))),
+            None => Err(AppError::NotFound(format!("Comment '{id}' not found"))),
+            _ => Err(AppError::NotFound(format!(
+                "Comment '{id}' doesn't have user"
+            ))),
+        }
+    }
+
+    async fn get_comments(&self, post_id: Uuid) -> Result<Vec<comment::Model>, AppError> {
+        let result = comment::Entity::find()
+            .filter(comment::Column::PostId.eq(post_id))
+            .order_by_asc(comment::Column::Id)
+            .find_also_related(user::Entity)
+            .all(&self.client)
+            .await?;
+        let comments = result
+            .into_iter()
+            .filter_map(|(comment, user)| {
+                Some(comment.set_user(user?.set_password("".to_string())))
+            })
+            .collect();
+        Ok(comments)
+    }
+
+    async fn update_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        comment::Entity::update_many()
+            .col_expr(comment::Column::Body, Expr::value(&comment.body))
+            .col_expr(comment::Column::UpdatedAt, Expr::value(comment.updated_at))
+            .filter(comment::Column::Id.eq(comment.id))
+            .exec(&self.client)
             .await?;
+        Ok(comment)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for PostgresRepository {
+    async fn delete_login_attempt(&self, id: String) -> Result<(), AppError> {
+        login_attempt::Entity::delete_by_id(id)
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_login_attempt(
+        &self,
+        id: String,
+    ) -> Result<Option<login_attempt::Model>, AppError> {
+        let attempt = login_attempt::Entity::find_by_id(id)
+            .filter(login_attempt::Column::ExpiresAt.gt(chrono::Utc::now()))
+            .one(&self.client)
+            .await?;
+        Ok(attempt)
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<login_attempt::Model, AppError> {
+        let now = chrono::Utc::now();
+        let attempt = login_attempt::Model {
+            id,
+            failures: 1,
+            last_failed_at: now,
+            expires_at,
+        };
+        // Counted in the upsert itself so concurrent failures can't overwrite each other.
+        let table = login_attempt::Entity;
+        let failures = Expr::case(
+            Expr::col((table, login_attempt::Column::ExpiresAt)).gt(now),
+            Expr::col((table, login_attempt::Column::Failures)).add(1),
+        )
+        .finally(1);
+        let attempt = login_attempt::Entity::insert(attempt.into_active_model())
+            .on_conflict(
+                OnConflict::column(login_attempt::Column::Id)
+                    .value(login_attempt::Column::Failures, failures)
+                    .update_columns([
+                        login_attempt::Column::LastFailedAt,
+                        login_attempt::Column::ExpiresAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(&self.client)
+            .await?;
+        Ok(attempt)
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for PostgresRepository {
+    async fn create_one_time_token(
+        &self,
+        token: one_time_token::Model,
+    ) -> Result<one_time_token::Model, AppError> {
+        let token = token.into_active_model().insert(&self.client).await?;
+        Ok(token)
+    }
 
-        schema = Schema::new(builder).create_table_from_entity(post::Entity);
-        self.client
-            .execute(builder.build(schema.if_not_exists()))
+    async fn delete_one_time_tokens(
+        &self,
+        user_id: Uuid,
+        purpose: one_time_token::Purpose,
+    ) -> Result<(), AppError> {
+        one_time_token::Entity::delete_many()
+            .filter(one_time_token::Column::UserId.eq(user_id))
+            .filter(one_time_token::Column::Purpose.eq(purpose))
+            .exec(&self.client)
             .await?;
         Ok(())
     }
+
+    async fn get_one_time_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<one_time_token::Model, AppError> {
+        let result = one_time_token::Entity::find()
+            .filter(one_time_token::Column::TokenHash.eq(token_hash))
+            .one(&self.client)
+            .await?;
+        match result {
+            Some(token) => Ok(token),
+            None => Err(AppError::NotFound("Token not found".to_string())),
+        }
+    }
+
+    async fn mark_one_time_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let result = one_time_token::Entity::update_many()
+            .col_expr(
+                one_time_token::Column::UsedAt,
+                Expr::value(Some(chrono::Utc::now())),
+            )
+            .filter(one_time_token::Column::Id.eq(id))
+            .filter(one_time_token::Column::UsedAt.is_null())
+            .exec(&self.client)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
 }
 
 #[async_trait]
@@ -54,6 +290,7 @@ impl PostRepository for PostgresRepository {
         Ok(post)
     }
 
+    /// Comments and reactions go with the post through their `post_id` foreign keys.
     async fn delete_post_by_id(&self, id: Uuid) -> Result<(), AppError> {
         post::Entity::delete_by_id(id).exec(&self.client).await?;
         Ok(())
@@ -73,19 +310,54 @@ impl PostRepository for PostgresRepository {
         }
     }
 
-    async fn get_posts(&self, filter: post::Pagination) -> Result<Vec<post::Model>, AppError> {
-        let mut query = post::Entity::find()
-            .limit(filter.limit)
-            .offset(filter.offset)
-            .order_by_desc(post::Column::Id);
-        if let Some(user_id) = filter.user_id {
-            query = query.filter(post::Column::UserId.eq(user_id));
+    async fn get_posts(
+        &self,
+        filter: post::Pagination,
+    ) -> Result<(Vec<post::Model>, u64), AppError> {
+        let mut query = post::Entity::find().filter(post_condition(&filter.filter));
+        let total = query.clone().count(&self.client).await?;
+
+        query = query.limit(filter.limit).offset(filter.offset);
+        if let Some(after) = filter.after {
+            query = query.filter(post::Column::Id.lt(after.0));
         }
+        query = match (filter.sort, filter.before) {
+            (Some(sort), _) => {
+                let order = if sort.descending {
+                    Order::Desc
+                } else {
+                    Order::Asc
+                };
+                // `LOWER` so SQLite, which compares bytes, orders titles like Postgres.
+                let key: SimpleExpr = match sort.field {
+                    post::SortField::CreatedAt => {
+                        Expr::col((post::Entity, post::Column::CreatedAt)).into()
+                    }
+                    post::SortField::Title => {
+                        Func::lower(Expr::col((post::Entity, post::Column::Title))).into()
+                    }
+                    post::SortField::UpdatedAt => {
+                        Expr::col((post::Entity, post::Column::UpdatedAt)).into()
+                    }
+                };
+                query
+                    .order_by(key, order.clone())
+                    .order_by(post::Column::Id, order)
+            }
+            // The posts closest to a `before` cursor are the oldest of those newer than it.
+            (None, Some(before)) => query
+                .filter(post::Column::Id.gt(before.0))
+                .order_by_asc(post::Column::Id),
+            (None, None) => query.order_by_desc(post::Column::Id),
+        };
 
-        let result = query
+        let mut result = query
             .find_also_related(user::Entity)
             .all(&self.client)
             .await?;
+        if filter.before.is_some() {
+            result.reverse();
+        }
         let mut posts = vec![];
         for (mut post, user) in result {
             if user.is_some() {
@@ -94,19 +366,359 @@ impl PostRepository for PostgresRepository {
             }
         }
 
-        Ok(posts)
+        Ok((posts, total))
+    }
+
+    async fn search_posts(
+        &self,
+        query: post::SearchQuery,
+    ) -> Result<(Vec<post::SearchResult>, u64), AppError> {
+        #[derive(FromQueryResult)]
+        struct Hit {
+            id: Uuid,
+            rank: f64,
+            snippet: String,
+        }
+
+        let backend = self.client.get_database_backend();
+        let mut values: Vec<Value> = vec![query.q.clone().into()];
+        let mut from = "FROM posts, websearch_to_tsquery('english', $1) query \
+            WHERE search_vector @@ query"
+            .to_string();
+        if let Some(user_id) = query.user_id {
+            values.push(user_id.into());
+            from.push_str(" AND user_id = $2");
+        }
+
+        let sql = format!("SELECT count(*) AS total {from}");
+        let total = self
+            .client
+            .query_one(Statement::from_sql_and_values(backend, sql, values.clone()))
+            .await?
+            .map_or(Ok(0), |row| row.try_get::<i64>("", "total"))?;
+
+        // Content is escaped after it is cut, so the headline marks matches with characters
+        // that escaping leaves alone.
+        values.push(
+            format!(
+                "StartSel={}, StopSel={}",
+                post::SNIPPET_START,
+                post::SNIPPET_STOP
+            )
+            .into(),
+        );
+        let sql = format!(
+            "SELECT id, ts_rank(search_vector, query)::float8 AS rank, \
+                ts_headline('english', content, query, ${}) \
+                AS snippet {from} ORDER BY rank DESC, id DESC LIMIT {} OFFSET {}",
+            values.len(),
+            query.limit,
+            query.offset
+        );
+        let hits = Hit::find_by_statement(Statement::from_sql_and_values(backend, sql, values))
+            .all(&self.client)
+            .await?;
+        let mut posts: HashMap<Uuid, post::Model> = post::Entity::find()
+            .filter(post::Column::Id.is_in(hits.iter().map(|hit| hit.id)))
+            .find_also_related(user::Entity)
+            .all(&self.client)
+            .await?
+            .into_iter()
+            .filter_map(|(post, user)| {
+                Some((post.id, post.set_user(user?.set_password("".to_string()))))
+            })
+            .collect();
+
+        let results = hits
+            .into_iter()
+            .filter_map(|hit| {
+                Some(post::SearchResult {
+                    post: posts.remove(&hit.id)?,
+                    rank: hit.rank,
+                    snippet: post::mark_snippet(&hit.snippet),
+                })
+            })
+            .collect();
+        Ok((results, total as u64))
     }
 
     async fn update_post(&self, post: post::Model) -> Result<post::Model, AppError> {
-        let post = post
-            .into_active_model()
-            .reset_all()
-            .update(&self.client)
+        // Posts loaded with their user have a nil `user_id`, so only the editable columns
+        // are written, like the other backends do.
+        post::Entity::update_many()
+            .col_expr(post::Column::Content, Expr::value(&post.content))
+            .col_expr(post::Column::Title, Expr::value(&post.title))
+            .col_expr(post::Column::UpdatedAt, Expr::value(post.updated_at))
+            .filter(post::Column::Id.eq(post.id))
+            .exec(&self.client)
             .await?;
         Ok(post)
     }
 }
 
+#[async_trait]
+impl ReactionRepository for PostgresRepository {
+    async fn create_reaction(&self, reaction: reaction::Model) -> Result<(), AppError> {
+        let columns = [
+            reaction::Column::PostId,
+            reaction::Column::UserId,
+            reaction::Column::Kind,
+        ];
+        reaction::Entity::insert(reaction.into_active_model())
+            .on_conflict(OnConflict::columns(columns).do_nothing().to_owned())
+            .exec_without_returning(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: reaction::Kind,
+    ) -> Result<(), AppError> {
+        reaction::Entity::delete_many()
+            .filter(reaction::Column::PostId.eq(post_id))
+            .filter(reaction::Column::UserId.eq(user_id))
+            .filter(reaction::Column::Kind.eq(kind))
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_reaction_summaries(
+        &self,
+        post_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, reaction::Summary>, AppError> {
+        #[derive(FromQueryResult)]
+        struct Count {
+            post_id: Uuid,
+            kind: reaction::Kind,
+            count: i64,
+        }
+
+        let counts = reaction::Entity::find()
+            .select_only()
+            .column(reaction::Column::PostId)
+            .column(reaction::Column::Kind)
+            .column_as(Expr::col(reaction::Column::Id).count(), "count")
+            .filter(reaction::Column::PostId.is_in(post_ids.clone()))
+            .group_by(reaction::Column::PostId)
+            .group_by(reaction::Column::Kind)
+            .into_model::<Count>()
+            .all(&self.client)
+            .await?;
+        let mine = reaction::Entity::find()
+            .filter(reaction::Column::PostId.is_in(post_ids))
+            .filter(reaction::Column::UserId.eq(user_id))
+            .all(&self.client)
+            .await?;
+
+        let mut summaries: HashMap<Uuid, reaction::Summary> = HashMap::new();
+        for Count {
+            post_id,
+            kind,
+            count,
+        } in counts
+        {
+            let summary = summaries.entry(post_id).or_default();
+            summary.counts.insert(kind, count as u64);
+        }
+        for reaction in mine {
+            let summary = summaries.entry(reaction.post_id).or_default();
+            summary.mine.push(reaction.kind);
+        }
+        for summary in summaries.values_mut() {
+            summary.mine.sort();
+        }
+        Ok(summaries)
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PostgresRepository {
+    async fn create_refresh_token(
+        &self,
+        token: refresh_token::Model,
+    ) -> Result<refresh_token::Model, AppError> {
+        let token = token.into_active_model().insert(&self.client).await?;
+        Ok(token)
+    }
+
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<refresh_token::Model, AppError> {
+        let result = refresh_token::Entity::find()
+            .filter(refresh_token::Column::TokenHash.eq(token_hash))
+            .one(&self.client)
+            .await?;
+        match result {
+            Some(token) => Ok(token),
+            None => Err(AppError::NotFound("Refresh token not found".to_string())),
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        let result = refresh_token::Entity::update_many()
+            .col_expr(
+                refresh_token::Column::UsedAt,
+                Expr::value(Some(chrono::Utc::now())),
+            )
+            .filter(refresh_token::Column::Id.eq(id))
+            .filter(refresh_token::Column::UsedAt.is_null())
+            .exec(&self.client)
+            .await?;
+        Ok(result.rows_affected == 1)
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        refresh_token::Entity::update_many()
+            .col_expr(
+                refresh_token::Column::RevokedAt,
+                Expr::value(Some(chrono::Utc::now())),
+            )
+            .filter(refresh_token::Column::FamilyId.eq(family_id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke_user_refresh_tokens(&self, user_id: Uuid) -> Result<(), AppError> {
+        refresh_token::Entity::update_many()
+            .col_expr(
+                refresh_token::Column::RevokedAt,
+                Expr::value(Some(chrono::Utc::now())),
+            )
+            .filter(refresh_token::Column::UserId.eq(user_id))
+            .filter(refresh_token::Column::RevokedAt.is_null())
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for PostgresRepository {
+    async fn create_revoked_token(&self, token: revoked_token::Model) -> Result<(), AppError> {
+        revoked_token::Entity::insert(token.into_active_model())
+            .on_conflict(
+                OnConflict::column(revoked_token::Column::Id)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<(), AppError> {
+        revoked_token::Entity::delete_many()
+            .filter(revoked_token::Column::ExpiresAt.lte(chrono::Utc::now()))
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, id: Uuid) -> Result<bool, AppError> {
+        let result = revoked_token::Entity::find_by_id(id)
+            .one(&self.client)
+            .await?;
+        Ok(result.is_some())
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PostgresRepository {
+    async fn create_session(&self, session: session::Model) -> Result<session::Model, AppError> {
+        let session = session.into_active_model().insert(&self.client).await?;
+        Ok(session)
+    }
+
+    async fn delete_expired_sessions(&self) -> Result<(), AppError> {
+        session::Entity::delete_many()
+            .filter(session::Column::ExpiresAt.lte(chrono::Utc::now()))
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = session::Entity::delete_many()
+            .filter(session::Column::Id.eq(id))
+            .filter(session::Column::UserId.eq(user_id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<(), AppError> {
+        session::Entity::delete_many()
+            .filter(session::Column::UserId.eq(user_id))
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_session_by_id(&self, id: Uuid) -> Result<session::Model, AppError> {
+        let result = session::Entity::find_by_id(id).one(&self.client).await?;
+        match result {
+            Some(session) => Ok(session),
+            None => Err(AppError::NotFound(format!("Session '{id}' not found"))),
+        }
+    }
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<session::Model>, AppError> {
+        let sessions = session::Entity::find()
+            .filter(session::Column::UserId.eq(user_id))
+            .filter(session::Column::ExpiresAt.gt(chrono::Utc::now()))
+            .order_by_asc(session::Column::Id)
+            .all(&self.client)
+            .await?;
+        Ok(sessions)
+    }
+
+    async fn update_session(&self, session: session::Model) -> Result<(), AppError> {
+        let result = session::Entity::update_many()
+            .col_expr(session::Column::ExpiresAt, Expr::value(session.expires_at))
+            .col_expr(session::Column::Ip, Expr::value(session.ip))
+            .col_expr(session::Column::Jti, Expr::value(session.jti))
+            .col_expr(
+                session::Column::LastSeenAt,
+                Expr::value(session.last_seen_at),
+            )
+            .col_expr(session::Column::UserAgent, Expr::value(session.user_agent))
+            .filter(session::Column::Id.eq(session.id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!(
+                "Session '{}' not found",
+                session.id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_session_last_seen(
+        &self,
+        id: Uuid,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError> {
+        session::Entity::update_many()
+            .col_expr(session::Column::LastSeenAt, Expr::value(last_seen_at))
+            .filter(session::Column::Id.eq(id))
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl UserRepository for PostgresRepository {
     async fn create_user(&self, user: user::Model) -> Result<user::Model, AppError> {
@@ -114,8 +726,43 @@ impl UserRepository for PostgresRepository {
         Ok(user)
     }
 
-    async fn delete_user_by_id(&self, id: Uuid) -> Result<(), AppError> {
-        user::Entity::delete_by_id(id).exec(&self.client).await?;
+    /// Tokens and sessions are removed by their `ON DELETE CASCADE` foreign keys.
+    async fn delete_user_by_id(
+        &self,
+        id: Uuid,
+        posts: user::OrphanedPosts,
+    ) -> Result<(), AppError> {
+        let txn = self.client.begin().await?;
+        match posts {
+            user::OrphanedPosts::Delete => {
+                comment::Entity::delete_many()
+                    .filter(comment::Column::UserId.eq(id))
+                    .exec(&txn)
+                    .await?;
+                post::Entity::delete_many()
+                    .filter(post::Column::UserId.eq(id))
+                    .exec(&txn)
+                    .await?;
+            }
+            user::OrphanedPosts::Reassign => {
+                user::Entity::insert(user::Model::tombstone().into_active_model())
+                    .on_conflict(OnConflict::column(user::Column::Id).do_nothing().to_owned())
+                    .exec_without_returning(&txn)
+                    .await?;
+                comment::Entity::update_many()
+                    .col_expr(comment::Column::UserId, Expr::value(user::TOMBSTONE_ID))
+                    .filter(comment::Column::UserId.eq(id))
+                    .exec(&txn)
+                    .await?;
+                post::Entity::update_many()
+                    .col_expr(post::Column::UserId, Expr::value(user::TOMBSTONE_ID))
+                    .filter(post::Column::UserId.eq(id))
+                    .exec(&txn)
+                    .await?;
+            }
+        }
+        user::Entity::delete_by_id(id).exec(&txn).await?;
+        txn.commit().await?;
         Ok(())
     }
 
@@ -137,6 +784,98 @@ impl UserRepository for PostgresRepository {
             None => Err(AppError::NotFound(format!("User '{id}' not found"))),
         }
     }
+
+    async fn mark_user_mfa_step_used(&self, id: Uuid, step: i64) -> Result<bool, AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::MfaLastStep, Expr::value(step))
+            .filter(user::Column::Id.eq(id))
+            .filter(
+                Condition::any()
+                    .add(user::Column::MfaLastStep.is_null())
+                    .add(user::Column::MfaLastStep.lt(step)),
+            )
+            .exec(&self.client)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn update_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        let id = user.id;
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::Email, Expr::value(user.email))
+            .col_expr(
+                user::Column::EmailVerifiedAt,
+                Expr::value(user.email_verified_at),
+            )
+            .col_expr(user::Column::Name, Expr::value(user.name))
+            .filter(user::Column::Id.eq(id))
+            .exec(&self.client)
+            .await
+            .map_err(|err| match AppError::from(err) {
+                AppError::Conflict(_) => AppError::Conflict("Email is already in use".to_string()),
+                err => err,
+            })?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => self.get_user_by_id(id).await,
+        }
+    }
+
+    async fn update_user_mfa(
+        &self,
+        id: Uuid,
+        secret: Option<String>,
+        enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::MfaEnabledAt, Expr::value(enabled_at))
+            .col_expr(user::Column::MfaSecret, Expr::value(secret))
+            .filter(user::Column::Id.eq(id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_user_password(&self, id: Uuid, password: String) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::Password, Expr::value(password))
+            .filter(user::Column::Id.eq(id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn update_user_role(&self, id: Uuid, role: user::Role) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::Role, Expr::value(role))
+            .filter(user::Column::Id.eq(id))
+            .exec(&self.client)
+            .await?;
+        match result.rows_affected {
+            0 => Err(AppError::NotFound(format!("User '{id}' not found"))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn verify_user_email(&self, id: Uuid) -> Result<(), AppError> {
+        self.get_user_by_id(id).await?;
+        user::Entity::update_many()
+            .col_expr(
+                user::Column::EmailVerifiedAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(user::Column::Id.eq(id))
+            .filter(user::Column::EmailVerifiedAt.is_null())
+            .exec(&self.client)
+            .await?;
+        Ok(())
+    }
 }
 
 impl PostgresRepository {
@@ -144,3 +883,34 @@ impl PostgresRepository {
         Arc::new(PostgresRepository { client })
     }
 }
+
+/// `post::Filter` as a condition, `LOWER` so the title prefix ignores case in SQLite too.
+fn post_condition(filter: &post::Filter) -> Condition {
+    let mut condition = Condition::all();
+    if let Some(at) = filter.created_after {
+        condition = condition.add(post::Column::CreatedAt.gt(at));
+    }
+    if let Some(at) = filter.created_before {
+        condition = condition.add(post::Column::CreatedAt.lt(at));
+    }
+    if let Some(prefix) = &filter.title_prefix {
+        let prefix = prefix
+            .to_lowercase()
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = LikeExpr::new(format!("{prefix}%")).escape('\\');
+        let title = Expr::expr(Func::lower(Expr::col(post::Column::Title)));
+        condition = condition.add(title.like(pattern));
+    }
+    if let Some(at) = filter.updated_after {
+        condition = condition.add(post::Column::UpdatedAt.gt(at));
+    }
+    if let Some(at) = filter.updated_before {
+        condition = condition.add(post::Column::UpdatedAt.lt(at));
+    }
+    if let Some(user_id) = filter.user_id {
+        condition = condition.add(post::Column::UserId.eq(user_id));
+    }
+    condition
+}
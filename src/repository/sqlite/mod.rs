@@ -0,0 +1,391 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use sea_orm::{Condition, ConnectOptions, DatabaseConnection, prelude::*};
+
+use crate::entity::*;
+use crate::repository::{postgres::PostgresRepository, *};
+
+/// Long enough to never elapse, `Duration::MAX` overflows when sqlx adds it to an `Instant`.
+const NEVER: Duration = Duration::from_secs(u32::MAX as u64);
+
+/// SQLite shares the sea-orm entities and queries with Postgres, so every call is
+/// delegated to `PostgresRepository` and only connection handling lives here.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    inner: PostgresRepository,
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn check_health(&self) -> Result<(), AppError> {
+        self.inner.check_health().await
+    }
+
+    async fn clear(&self) -> Result<(), AppError> {
+        self.inner.clear().await
+    }
+
+    async fn close(&self) -> Result<(), AppError> {
+        self.inner.close().await
+    }
+
+    async fn init(&self) -> Result<(), AppError> {
+        self.inner.init().await
+    }
+
+    async fn migrate_down(&self, steps: Option<u32>) -> Result<(), AppError> {
+        self.inner.migrate_down(steps).await
+    }
+
+    async fn migrate_up(&self, steps: Option<u32>) -> Result<(), AppError> {
+        self.inner.migrate_up(steps).await
+    }
+
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>, AppError> {
+        self.inner.migration_status().await
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for SqliteRepository {
+    async fn create_api_key(&self, key: api_key::Model) -> Result<api_key::Model, AppError> {
+        self.inner.create_api_key(key).await
+    }
+
+    async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.inner.delete_api_key(id, user_id).await
+    }
+
+    async fn get_api_key_by_hash(&self, key_hash: String) -> Result<api_key::Model, AppError> {
+        self.inner.get_api_key_by_hash(key_hash).await
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<api_key::Model>, AppError> {
+        self.inner.get_api_keys(user_id).await
+    }
+}
+
+#[async_trait]
+impl CommentRepository for SqliteRepository {
+    async fn create_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        self.inner.create_comment(comment).await
+    }
+
+    async fn delete_comment_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        self.inner.delete_comment_by_id(id).await
+    }
+
+    async fn get_comment_by_id(&self, id: Uuid) -> Result<comment::Model, AppError> {
+        self.inner.get_comment_by_id(id).await
+    }
+
+    async fn get_comments(&self, post_id: Uuid) -> Result<Vec<comment::Model>, AppError> {
+        self.inner.get_comments(post_id).await
+    }
+
+    async fn update_comment(&self, comment: comment::Model) -> Result<comment::Model, AppError> {
+        self.inner.update_comment(comment).await
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for SqliteRepository {
+    async fn delete_login_attempt(&self, id: String) -> Result<(), AppError> {
+        self.inner.delete_login_attempt(id).await
+    }
+
+    async fn get_login_attempt(
+        &self,
+        id: String,
+    ) -> Result<Option<login_attempt::Model>, AppError> {
+        self.inner.get_login_attempt(id).await
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<login_attempt::Model, AppError> {
+        self.inner.record_failed_login(id, expires_at).await
+    }
+}
+
+#[async_trait]
+impl OneTimeTokenRepository for SqliteRepository {
+    async fn create_one_time_token(
+        &self,
+        token: one_time_token::Model,
+    ) -> Result<one_time_token::Model, AppError> {
+        self.inner.create_one_time_token(token).await
+    }
+
+    async fn delete_one_time_tokens(
+        &self,
+        user_id: Uuid,
+        purpose: one_time_token::Purpose,
+    ) -> Result<(), AppError> {
+        self.inner.delete_one_time_tokens(user_id, purpose).await
+    }
+
+    async fn get_one_time_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<one_time_token::Model, AppError> {
+        self.inner.get_one_time_token_by_hash(token_hash).await
+    }
+
+    async fn mark_one_time_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        self.inner.mark_one_time_token_used(id).await
+    }
+}
+
+#[async_trait]
+impl PostRepository for SqliteRepository {
+    async fn create_post(&self, post: post::Model) -> Result<post::Model, AppError> {
+        self.inner.create_post(post).await
+    }
+
+    async fn delete_post_by_id(&self, id: Uuid) -> Result<(), AppError> {
+        self.inner.delete_post_by_id(id).await
+    }
+
+    async fn get_post_by_id(&self, id: Uuid) -> Result<post::Model, AppError> {
+        self.inner.get_post_by_id(id).await
+    }
+
+    async fn get_posts(
+        &self,
+        filter: post::Pagination,
+    ) -> Result<(Vec<post::Model>, u64), AppError> {
+        self.inner.get_posts(filter).await
+    }
+
+    /// SQLite has no full-text index here, so posts containing every term are ranked
+    /// the way `MemoryRepository` ranks them.
+    async fn search_posts(
+        &self,
+        query: post::SearchQuery,
+    ) -> Result<(Vec<post::SearchResult>, u64), AppError> {
+        let terms = query.terms();
+        if terms.is_empty() {
+            return Ok((vec![], 0));
+        }
+        let mut condition = Condition::all();
+        for term in terms {
+            condition = condition.add(
+                Condition::any()
+                    .add(post::Column::Title.contains(&term))
+                    .add(post::Column::Content.contains(&term)),
+            );
+        }
+        if let Some(user_id) = query.user_id {
+            condition = condition.add(post::Column::UserId.eq(user_id));
+        }
+
+        let candidates = post::Entity::find()
+            .filter(condition)
+            .find_also_related(user::Entity)
+            .all(&self.inner.client)
+            .await?
+            .into_iter()
+            .filter_map(|(post, user)| Some(post.set_user(user?.set_password("".to_string()))));
+        Ok(post::search(candidates, &query))
+    }
+
+    async fn update_post(&self, post: post::Model) -> Result<post::Model, AppError> {
+        self.inner.update_post(post).await
+    }
+}
+
+#[async_trait]
+impl ReactionRepository for SqliteRepository {
+    async fn create_reaction(&self, reaction: reaction::Model) -> Result<(), AppError> {
+        self.inner.create_reaction(reaction).await
+    }
+
+    async fn delete_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: reaction::Kind,
+    ) -> Result<(), AppError> {
+        self.inner.delete_reaction(post_id, user_id, kind).await
+    }
+
+    async fn get_reaction_summaries(
+        &self,
+        post_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<HashMap<Uuid, reaction::Summary>, AppError> {
+        self.inner.get_reaction_summaries(post_ids, user_id).await
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for SqliteRepository {
+    async fn create_refresh_token(
+        &self,
+        token: refresh_token::Model,
+    ) -> Result<refresh_token::Model, AppError> {
+        self.inner.create_refresh_token(token).await
+    }
+
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> Result<refresh_token::Model, AppError> {
+        self.inner.get_refresh_token_by_hash(token_hash).await
+    }
+
+    async fn mark_refresh_token_used(&self, id: Uuid) -> Result<bool, AppError> {
+        self.inner.mark_refresh_token_used(id).await
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: Uuid) -> Result<(), AppError> {
+        self.inner.revoke_refresh_token_family(family_id).await
+    }
+
+    async fn revoke_user_refresh_tokens(&self, user_id: Uuid) -> Result<(), AppError> {
+        self.inner.revoke_user_refresh_tokens(user_id).await
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for SqliteRepository {
+    async fn create_revoked_token(&self, token: revoked_token::Model) -> Result<(), AppError> {
+        self.inner.create_revoked_token(token).await
+    }
+
+    async fn delete_expired_revoked_tokens(&self) -> Result<(), AppError> {
+        self.inner.delete_expired_revoked_tokens().await
+    }
+
+    async fn is_token_revoked(&self, id: Uuid) -> Result<bool, AppError> {
+        self.inner.is_token_revoked(id).await
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SqliteRepository {
+    async fn create_session(&self, session: session::Model) -> Result<session::Model, AppError> {
+        self.inner.create_session(session).await
+    }
+
+    async fn delete_expired_sessions(&self) -> Result<(), AppError> {
+        self.inner.delete_expired_sessions().await
+    }
+
+    async fn delete_session(&self, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        self.inner.delete_session(id, user_id).await
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> Result<(), AppError> {
+        self.inner.delete_user_sessions(user_id).await
+    }
+
+    async fn get_session_by_id(&self, id: Uuid) -> Result<session::Model, AppError> {
+        self.inner.get_session_by_id(id).await
+    }
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<session::Model>, AppError> {
+        self.inner.get_sessions(user_id).await
+    }
+
+    async fn update_session(&self, session: session::Model) -> Result<(), AppError> {
+        self.inner.update_session(session).await
+    }
+
+    async fn update_session_last_seen(
+        &self,
+        id: Uuid,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError> {
+        self.inner.update_session_last_seen(id, last_seen_at).await
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteRepository {
+    async fn create_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        self.inner.create_user(user).await
+    }
+
+    async fn delete_user_by_id(
+        &self,
+        id: Uuid,
+        posts: user::OrphanedPosts,
+    ) -> Result<(), AppError> {
+        self.inner.delete_user_by_id(id, posts).await
+    }
+
+    async fn get_user_by_email(&self, email: String) -> Result<user::Model, AppError> {
+        self.inner.get_user_by_email(email).await
+    }
+
+    async fn get_user_by_id(&self, id: Uuid) -> Result<user::Model, AppError> {
+        self.inner.get_user_by_id(id).await
+    }
+
+    async fn mark_user_mfa_step_used(&self, id: Uuid, step: i64) -> Result<bool, AppError> {
+        self.inner.mark_user_mfa_step_used(id, step).await
+    }
+
+    async fn update_user(&self, user: user::Model) -> Result<user::Model, AppError> {
+        self.inner.update_user(user).await
+    }
+
+    async fn update_user_mfa(
+        &self,
+        id: Uuid,
+        secret: Option<String>,
+        enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<(), AppError> {
+        self.inner.update_user_mfa(id, secret, enabled_at).await
+    }
+
+    async fn update_user_password(&self, id: Uuid, password: String) -> Result<(), AppError> {
+        self.inner.update_user_password(id, password).await
+    }
+
+    async fn update_user_role(&self, id: Uuid, role: user::Role) -> Result<(), AppError> {
+        self.inner.update_user_role(id, role).await
+    }
+
+    async fn verify_user_email(&self, id: Uuid) -> Result<(), AppError> {
+        self.inner.verify_user_email(id).await
+    }
+}
+
+impl SqliteRepository {
+    pub fn new(client: DatabaseConnection) -> Self {
+        SqliteRepository {
+            inner: PostgresRepository { client },
+        }
+    }
+
+    pub fn into_shared(self) -> Arc<dyn Repository> {
+        Arc::new(self)
+    }
+
+    /// Accepts either a full `sqlite:` URL, a file path or `:memory:`. File databases
+    /// are created on first use. An in-memory database only lives as long as its
+    /// connection, so the pool is pinned to a single connection that is never recycled.
+    pub fn connect_options(url: String) -> ConnectOptions {
+        let url = match url.as_str() {
+            ":memory:" => "sqlite::memory:".to_string(),
+            path if !path.starts_with("sqlite:") => format!("sqlite://{path}?mode=rwc"),
+            _ => url,
+        };
+
+        let mut options = ConnectOptions::new(url.clone());
+        if url.contains(":memory:") || url.contains("mode=memory") {
+            options
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(NEVER)
+                .max_lifetime(NEVER);
+        }
+        options
+    }
+}
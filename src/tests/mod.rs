@@ -1,2 +1,212 @@
 mod actix;
 mod axum;
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use ::axum::{
+    Form, Json, Router,
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+};
+use ed25519_dalek::pkcs8::{EncodePrivateKey, spki::der::pem::LineEnding};
+use jsonwebtoken::{Algorithm, EncodingKey, Header, jwk::JwkSet};
+use serde_json::{Value, json};
+
+use crate::{AppState, adapter, config, entity::user, repository};
+
+const MOCK_CLIENT_ID: &str = "rust-rest-api";
+/// The only authorization code the mock issuer accepts.
+const MOCK_CODE: &str = "mock-code";
+
+/// Gives every test its own repository so the suite can run in parallel. The in-memory
+/// backend is used unless `DATABASE_TYPE` points the tests at a live database.
+async fn new_repo() -> Arc<dyn repository::Repository> {
+    if config::database_type().is_empty() {
+        return repository::MemoryRepository::default().into_shared();
+    }
+
+    repository::new().await.unwrap()
+}
+
+/// An app state over a fresh repository, logging mail and without OIDC.
+async fn new_state() -> AppState {
+    let repo = new_repo().await;
+    AppState {
+        auth: adapter::Auth::new(repo.clone()),
+        mailer: adapter::LogMailer.into_shared(),
+        oidc: None,
+        repo,
+    }
+}
+
+/// Creates a user with the password `testing@123` and starts a session for them,
+/// returning the user and their access token.
+async fn register_and_login(state: &AppState, name: &str, email: &str) -> (user::Model, String) {
+    let password = state.auth.hash_password("testing@123").unwrap();
+    let user = state
+        .repo
+        .create_user(user::Model {
+            email: email.to_string(),
+            name: name.to_string(),
+            password,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let (token, _) = state
+        .auth
+        .start_session(&user, None, String::new())
+        .await
+        .unwrap();
+    (user, token)
+}
+
+/// Empties the repository for the next test and closes its connections, which outlive
+/// the test otherwise as actix keeps its runtime around.
+async fn drop_repo(repo: Arc<dyn repository::Repository>) {
+    let result = repo.clear().await;
+    assert!(result.is_ok());
+    let result = repo.close().await;
+    assert!(result.is_ok());
+}
+
+/// Reads the mail a `FileMailer` wrote to `dir`, oldest first.
+fn read_mail(dir: &Path) -> Vec<adapter::Mail> {
+    let mut paths: Vec<_> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.map(|entry| entry.unwrap().path()).collect(),
+        Err(_) => vec![],
+    };
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap())
+        .collect()
+}
+
+/// The TOTP code an authenticator app enrolled with `secret` would show right now.
+fn totp_code(secret: &str) -> String {
+    let secret = totp_rs::Secret::try_from_base32(secret).unwrap();
+    let totp = totp_rs::Builder::new().with_secret(secret).build().unwrap();
+    totp.generate_current().to_string()
+}
+
+/// An Argon2id hash of `password` with the lowest costs the algorithm allows.
+fn weak_password_hash(password: &str) -> String {
+    use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version, password_hash::SaltString};
+
+    let params = Params::new(Params::MIN_M_COST, Params::MIN_T_COST, 1, None).unwrap();
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let salt = SaltString::generate(&mut argon2::password_hash::rand_core::OsRng);
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .unwrap()
+        .to_string()
+}
+
+/// The login the mock issuer hands an ID token out for, and the PKCE challenge it
+/// expects the code to be redeemed with.
+#[derive(Clone, Default)]
+struct MockLogin {
+    code_challenge: String,
+    email: String,
+    email_verified: bool,
+    nonce: String,
+}
+
+#[derive(Clone)]
+struct MockIssuer {
+    issuer: String,
+    login: Arc<Mutex<MockLogin>>,
+    pem: String,
+}
+
+/// Serves an OIDC issuer's discovery document, keys and token endpoint on a random local
+/// port, returning a client for it and the login its next ID token is for.
+async fn mock_issuer() -> (Arc<adapter::Oidc>, Arc<Mutex<MockLogin>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}", listener.local_addr().unwrap());
+    let pem = ed25519_dalek::SigningKey::from_bytes(&[9; 32])
+        .to_pkcs8_pem(LineEnding::LF)
+        .unwrap()
+        .to_string();
+    let login = Arc::new(Mutex::new(MockLogin::default()));
+    let state = MockIssuer {
+        issuer: issuer.clone(),
+        login: login.clone(),
+        pem,
+    };
+
+    let app = Router::new()
+        .route(
+            "/.well-known/openid-configuration",
+            get(|State(state): State<MockIssuer>| async move {
+                Json(json!({
+                    "authorization_endpoint": format!("{}/authorize", state.issuer),
+                    "issuer": state.issuer,
+                    "jwks_uri": format!("{}/jwks", state.issuer),
+                    "token_endpoint": format!("{}/token", state.issuer),
+                }))
+            }),
+        )
+        .route("/jwks", get(mock_jwks))
+        .route("/token", post(mock_token))
+        .with_state(state);
+    tokio::spawn(async move { ::axum::serve(listener, app).await.unwrap() });
+
+    let oidc = adapter::Oidc::new(
+        &issuer,
+        MOCK_CLIENT_ID,
+        "secret",
+        "http://localhost:5000/auth/oidc/callback",
+    );
+    (oidc, login)
+}
+
+async fn mock_jwks(State(state): State<MockIssuer>) -> Json<JwkSet> {
+    let key = adapter::Key::from_pem("mock", Algorithm::EdDSA, &state.pem).unwrap();
+    Json(adapter::Keyring::new(vec![key]).jwks())
+}
+
+async fn mock_token(
+    State(state): State<MockIssuer>,
+    Form(form): Form<HashMap<String, String>>,
+) -> (StatusCode, Json<Value>) {
+    use sha2::Digest;
+
+    let login = state.login.lock().unwrap().clone();
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    let challenge = base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        sha2::Sha256::digest(verifier.as_bytes()),
+    );
+    if form.get("code").map(String::as_str) != Some(MOCK_CODE) || challenge != login.code_challenge
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        );
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let claims = json!({
+        "aud": MOCK_CLIENT_ID,
+        "email": login.email,
+        "email_verified": login.email_verified,
+        "exp": now + 300,
+        "iat": now,
+        "iss": state.issuer,
+        "nonce": login.nonce,
+        "sub": "mock-subject",
+    });
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some("mock".to_string());
+    let key = EncodingKey::from_ed_pem(state.pem.as_bytes()).unwrap();
+    let id_token = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+    let body = json!({ "access_token": "mock", "id_token": id_token, "token_type": "Bearer" });
+    (StatusCode::OK, Json(body))
+}